@@ -3,8 +3,8 @@ use proc_macro::TokenStream;
 use proc_macro2::Ident;
 use quote::{format_ident, quote};
 use syn::{
-    parse_macro_input, Data, DeriveInput, Error, Field, Fields, FieldsNamed, GenericArgument,
-    LitStr, PathArguments, Result, Type,
+    parse_macro_input, Attribute, Data, DeriveInput, Error, ExprClosure, Field, Fields,
+    FieldsNamed, GenericArgument, LitStr, Pat, Path, PathArguments, Result, Type,
 };
 
 #[proc_macro_derive(Builder, attributes(builder))]
@@ -31,13 +31,27 @@ fn expand(input: DeriveInput) -> Result<proc_macro2::TokenStream> {
         }
     };
 
-    let setters = setter_methods(fields)?;
+    let options = builder_options(&input.attrs)?;
+    let (setters, build_method) = if options.const_builder {
+        (
+            const_setter_methods(fields)?,
+            const_build_method(fields, command_ident, &options)?,
+        )
+    } else {
+        (
+            setter_methods(fields)?,
+            build_method(fields, command_ident, &options),
+        )
+    };
+    let constness = if options.const_builder {
+        quote!(const)
+    } else {
+        quote!()
+    };
     let fields_def = fields_definitions(fields);
     let fields_default_value = fields_default_values(fields);
-    let build_method = build_method(fields, command_ident);
 
     let expand = quote! {
-        use std::error::Error;
         pub struct #command_builder_ident {
             #(#fields_def)*
         }
@@ -49,7 +63,7 @@ fn expand(input: DeriveInput) -> Result<proc_macro2::TokenStream> {
         }
 
         impl #command_ident{
-            pub fn builder() -> #command_builder_ident{
+            pub #constness fn builder() -> #command_builder_ident{
                 let builder = #command_builder_ident{
                     #(#fields_default_value)*
                 };
@@ -60,6 +74,85 @@ fn expand(input: DeriveInput) -> Result<proc_macro2::TokenStream> {
     Ok(expand)
 }
 
+/// Options given by `#[builder(...)]` attributes on the struct itself.
+struct BuilderOptions {
+    build_fn: BuildFnOptions,
+    /// `post_build = "path"`: a function applied to the built value.
+    post_build: Option<Path>,
+    /// `const`: generate `const fn` setters taking the builder by value and a
+    /// `const fn` build method that panics on missing fields.
+    const_builder: bool,
+}
+
+/// Options given by `#[builder(build_fn(...))]`.
+struct BuildFnOptions {
+    name: Ident,
+    /// Error type returned by the build method; it must implement
+    /// `From<&'static str>` to report missing fields.
+    error: Option<Type>,
+    /// Type returned by the build method, needed when `post_build` changes
+    /// the type of the built value.
+    output: Option<Type>,
+    private: bool,
+}
+
+fn builder_options(attrs: &[Attribute]) -> Result<BuilderOptions> {
+    let mut options = BuilderOptions {
+        build_fn: BuildFnOptions {
+            name: format_ident!("build"),
+            error: None,
+            output: None,
+            private: false,
+        },
+        post_build: None,
+        const_builder: false,
+    };
+    for attr in attrs {
+        if !attr.path().is_ident("builder") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("post_build") {
+                let liter: LitStr = meta.value()?.parse()?;
+                options.post_build = Some(liter.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("const") {
+                options.const_builder = true;
+                Ok(())
+            } else if meta.path.is_ident("build_fn") {
+                let build_fn = &mut options.build_fn;
+                meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        let liter: LitStr = meta.value()?.parse()?;
+                        build_fn.name = liter.parse()?;
+                        Ok(())
+                    } else if meta.path.is_ident("error") {
+                        let liter: LitStr = meta.value()?.parse()?;
+                        build_fn.error = Some(liter.parse()?);
+                        Ok(())
+                    } else if meta.path.is_ident("output") {
+                        let liter: LitStr = meta.value()?.parse()?;
+                        build_fn.output = Some(liter.parse()?);
+                        Ok(())
+                    } else if meta.path.is_ident("private") {
+                        build_fn.private = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error(
+                            "expected `name = \"...\"`, `error = \"...\"`, `output = \"...\"` or `private`",
+                        ))
+                    }
+                })
+            } else {
+                Err(meta.error(
+                    "expected `#[builder(build_fn(...))]`, `#[builder(post_build = \"...\")]` or `#[builder(const)]`",
+                ))
+            }
+        })?;
+    }
+    Ok(options)
+}
+
 fn fields_default_values(fields_named: &FieldsNamed) -> Vec<proc_macro2::TokenStream> {
     fields_named
         .named
@@ -93,6 +186,42 @@ fn fields_definitions(fields_named: &FieldsNamed) -> Vec<proc_macro2::TokenStrea
         .collect()
 }
 
+/// Options given by `#[builder(...)]` attributes on a field.
+struct FieldOptions {
+    /// `each = "..."`: name of a setter that appends one element.
+    each_ident: Option<String>,
+    /// `with = |...| ...`: closure turning the setter's arguments into the
+    /// field's value.
+    with: Option<ExprClosure>,
+}
+
+fn field_options(field: &Field) -> Result<FieldOptions> {
+    // if the filed has attribute `build`, we had add a one_at_once for this field
+    let mut each_ident: Option<String> = None;
+    let mut with: Option<ExprClosure> = None;
+    for attr in &field.attrs {
+        if attr.path().is_ident("builder") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("each") {
+                    let value = meta.value()?;
+                    let liter: LitStr = value.parse()?;
+                    each_ident = Some(liter.value());
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    with = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(Error::new_spanned(
+                        attr,
+                        "expected `#[builder(each = \"...\")]`",
+                    ))
+                }
+            })?;
+        }
+    }
+    Ok(FieldOptions { each_ident, with })
+}
+
 fn setter_methods(fields: &FieldsNamed) -> Result<Vec<proc_macro2::TokenStream>> {
     let mut methods = vec![];
     for field in fields.named.iter() {
@@ -102,27 +231,26 @@ fn setter_methods(fields: &FieldsNamed) -> Result<Vec<proc_macro2::TokenStream>>
         } else {
             inner_type_of_option(field)
         };
-        // if the filed has attribute `build`, we had add a one_at_once for this field
-        let mut each_ident: Option<String> = None;
-        for attr in &field.attrs {
-            if attr.path().is_ident("builder") {
-                attr.parse_nested_meta(|meta| {
-                    if meta.path.is_ident("each") {
-                        let value = meta.value()?;
-                        let liter: LitStr = value.parse()?;
-                        each_ident = Some(liter.value());
-                        Ok(())
-                    } else {
-                        Err(Error::new_spanned(
-                            attr,
-                            "expected `#[builder(each = \"...\")]`",
-                        ))
-                    }
-                })?;
-            }
-        }
+        let FieldOptions { each_ident, with } = field_options(field)?;
+        let setter = match with {
+            Some(ref closure) => with_setter(name, closure)?,
+            None => quote! {
+                 pub fn #name(&mut self, #name: #ty) -> &mut Self {
+                    self.#name = std::option::Option::Some(#name);
+                    self
+                 }
+            },
+        };
         let method = match each_ident {
-            Some(ref v) if *v == name.to_string() => {
+            Some(ref v) if name == v => {
+                // The `each` setter takes the field's name, leaving no setter
+                // for the closure.
+                if let Some(ref closure) = with {
+                    return Err(Error::new_spanned(
+                        closure,
+                        "`with` cannot be combined with an `each` setter named like the field",
+                    ));
+                }
                 let v = format_ident!("{}", v);
                 let inner_ty = inner_type_of_vec(field);
 
@@ -133,15 +261,12 @@ fn setter_methods(fields: &FieldsNamed) -> Result<Vec<proc_macro2::TokenStream>>
                     }
                 }
             }
-            Some(ref v) if *v != name.to_string() => {
+            Some(ref v) => {
                 let v = format_ident!("{}", v);
                 let inner_ty = inner_type_of_vec(field);
 
                 quote! {
-                     pub fn #name(&mut self, #name: #ty) -> &mut Self {
-                        self.#name = std::option::Option::Some(#name);
-                        self
-                     }
+                    #setter
 
                     pub fn #v(&mut self, #v: #inner_ty) -> &mut Self {
                         self.#name.get_or_insert(vec![]).push(#v);
@@ -149,19 +274,44 @@ fn setter_methods(fields: &FieldsNamed) -> Result<Vec<proc_macro2::TokenStream>>
                     }
                 }
             }
+            None => setter,
+        };
+        methods.push(method);
+    }
+    Ok(methods)
+}
 
+/// Setter for `#[builder(with = |a: A, b: B| -> T { ... })]`: the setter takes
+/// the closure's parameters and stores the closure's result.
+fn with_setter(name: &Ident, closure: &ExprClosure) -> Result<proc_macro2::TokenStream> {
+    let mut params = vec![];
+    let mut args = vec![];
+    for (i, input) in closure.inputs.iter().enumerate() {
+        let pat_type = match input {
+            Pat::Type(pat_type) => pat_type,
             _ => {
-                quote! {
-                     pub fn #name(&mut self, #name: #ty) -> &mut Self {
-                        self.#name = std::option::Option::Some(#name);
-                        self
-                     }
-                }
+                return Err(Error::new_spanned(
+                    input,
+                    "expected a closure parameter with a type, like `s: &str`",
+                ))
             }
         };
-        methods.push(method);
+        let arg = match *pat_type.pat {
+            Pat::Ident(ref pat) if pat.by_ref.is_none() && pat.subpat.is_none() => {
+                pat.ident.clone()
+            }
+            _ => format_ident!("arg{}", i),
+        };
+        let ty = &pat_type.ty;
+        params.push(quote!(#arg: #ty));
+        args.push(arg);
     }
-    Ok(methods)
+    Ok(quote! {
+        pub fn #name(&mut self, #(#params),*) -> &mut Self {
+            self.#name = std::option::Option::Some((#closure)(#(#args),*));
+            self
+        }
+    })
 }
 
 fn inner_type_of_vec(field: &Field) -> Type {
@@ -188,10 +338,14 @@ fn inner_type_of_vec(field: &Field) -> Type {
     }
 }
 
-fn build_method(fields: &FieldsNamed, command_ident: &Ident) -> proc_macro2::TokenStream {
+fn build_method(
+    fields: &FieldsNamed,
+    command_ident: &Ident,
+    options: &BuilderOptions,
+) -> proc_macro2::TokenStream {
     let field_check_and_set = fields.named.iter().map(|field| {
         let name = field.ident.as_ref().unwrap();
-        let err_msg = format!("field `{}` is missing", name.to_string());
+        let err_msg = format!("field `{}` is missing", name);
 
         if is_option(field) {
             quote! {
@@ -203,13 +357,117 @@ fn build_method(fields: &FieldsNamed, command_ident: &Ident) -> proc_macro2::Tok
             }
         }
     });
+
+    let build_fn = &options.build_fn;
+    let vis = if build_fn.private {
+        quote!()
+    } else {
+        quote!(pub)
+    };
+    let fn_name = &build_fn.name;
+    let error = match build_fn.error {
+        Some(ref error) => quote!(#error),
+        None => quote!(std::boxed::Box<dyn std::error::Error>),
+    };
+    let output = match build_fn.output {
+        Some(ref output) => quote!(#output),
+        None => quote!(#command_ident),
+    };
+    let built = match options.post_build {
+        Some(ref post_build) => quote!(#post_build(built)),
+        None => quote!(built),
+    };
     quote! {
-        pub fn build(&mut self) -> std::result::Result<#command_ident, std::boxed::Box<dyn Error>> {
-            std::result::Result::Ok(#command_ident{
+        #vis fn #fn_name(&mut self) -> std::result::Result<#output, #error> {
+            let built = #command_ident{
                 #(#field_check_and_set)*
-            })
+            };
+            std::result::Result::Ok(#built)
+        }
+    }
+}
+
+/// Setters of a `#[builder(const)]` builder take and return the builder by
+/// value so they can be chained in a constant expression.
+fn const_setter_methods(fields: &FieldsNamed) -> Result<Vec<proc_macro2::TokenStream>> {
+    let mut methods = vec![];
+    for field in fields.named.iter() {
+        let options = field_options(field)?;
+        if options.each_ident.is_some() || options.with.is_some() {
+            return Err(Error::new_spanned(
+                field,
+                "`each` and `with` are not supported with `#[builder(const)]`",
+            ));
         }
+        let name = field.ident.as_ref().unwrap();
+        let ty = if !is_option(field) {
+            field.ty.clone()
+        } else {
+            inner_type_of_option(field)
+        };
+        methods.push(quote! {
+            pub const fn #name(mut self, #name: #ty) -> Self {
+                self.#name = std::option::Option::Some(#name);
+                self
+            }
+        });
     }
+    Ok(methods)
+}
+
+fn const_build_method(
+    fields: &FieldsNamed,
+    command_ident: &Ident,
+    options: &BuilderOptions,
+) -> Result<proc_macro2::TokenStream> {
+    let build_fn = &options.build_fn;
+    if let Some(ref error) = build_fn.error {
+        return Err(Error::new_spanned(
+            error,
+            "a `#[builder(const)]` build method panics on missing fields and cannot return an error",
+        ));
+    }
+
+    let field_check_and_set = fields.named.iter().map(|field| {
+        let name = field.ident.as_ref().unwrap();
+        let err_msg = format!("field `{}` is missing", name);
+
+        if is_option(field) {
+            quote! {
+                #name: self.#name,
+            }
+        } else {
+            quote! {
+                #name: match self.#name {
+                    std::option::Option::Some(value) => value,
+                    std::option::Option::None => std::panic!(#err_msg),
+                },
+            }
+        }
+    });
+
+    let vis = if build_fn.private {
+        quote!()
+    } else {
+        quote!(pub)
+    };
+    let fn_name = &build_fn.name;
+    let output = match build_fn.output {
+        Some(ref output) => quote!(#output),
+        None => quote!(#command_ident),
+    };
+    let built = match options.post_build {
+        Some(ref post_build) => quote!(#post_build(built)),
+        None => quote!(built),
+    };
+    Ok(quote! {
+        #vis const fn #fn_name(self) -> #output {
+            let built = #command_ident{
+                #(#field_check_and_set)*
+            };
+            #built
+        }
+    })
 }
 
 fn is_option(field: &Field) -> bool {
@@ -219,9 +477,7 @@ fn is_option(field: &Field) -> bool {
             // eprintln!("{:#?}", type_path);
             type_path.path.segments[0].ident.eq("Option")
         }
-        _ => {
-            unimplemented!()
-        }
+        _ => false,
     }
 }
 
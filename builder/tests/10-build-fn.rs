@@ -0,0 +1,60 @@
+// The build method can be renamed, made private so the caller can wrap it,
+// and made to return the caller's own error type instead of
+// `Box<dyn Error>`. A `post_build` function transforms the built value
+// before it is returned, with `output` naming the resulting type.
+//
+// The custom error type must implement `From<&'static str>`; the message
+// describes the first missing field.
+
+use derive_builder::Builder;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Missing(&'static str),
+}
+
+impl From<&'static str> for ConfigError {
+    fn from(msg: &'static str) -> Self {
+        ConfigError::Missing(msg)
+    }
+}
+
+#[derive(Builder)]
+#[builder(build_fn(name = "finish", error = "ConfigError", private))]
+pub struct Command {
+    executable: String,
+    current_dir: Option<String>,
+}
+
+impl CommandBuilder {
+    pub fn build(&mut self) -> Result<Command, ConfigError> {
+        let mut command = self.finish()?;
+        command.current_dir.get_or_insert_with(|| ".".to_owned());
+        Ok(command)
+    }
+}
+
+#[derive(Builder)]
+#[builder(post_build = "Arc::new", build_fn(output = "Arc<Shared>"))]
+pub struct Shared {
+    name: String,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir.as_deref(), Some("."));
+
+    match Command::builder().build() {
+        Err(ConfigError::Missing(msg)) => assert_eq!(msg, "field `executable` is missing"),
+        Ok(_) => panic!("expected missing field error"),
+    }
+
+    let shared: Arc<Shared> = Shared::builder().name("x".to_owned()).build().unwrap();
+    assert_eq!(Arc::strong_count(&shared), 1);
+    assert_eq!(shared.name, "x");
+}
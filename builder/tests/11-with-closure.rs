@@ -0,0 +1,34 @@
+// A field annotated with `#[builder(with = |...| ...)]` gets a setter whose
+// parameters are those of the closure; the setter stores whatever the closure
+// returns. Every closure parameter needs a type annotation so the setter's
+// signature can be written out.
+//
+// For an Option field the closure returns the inner type, just like the
+// argument of the ordinary setter.
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(with = |dir: &str, file: &str| -> PathBuf { PathBuf::from(dir).join(file) })]
+    executable: PathBuf,
+    #[builder(with = |secs: u64| Duration::from_secs(secs))]
+    timeout: Duration,
+    #[builder(with = |(host, port): (&str, u16)| format!("{}:{}", host, port))]
+    remote: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("/usr/bin", "cargo")
+        .timeout(30)
+        .remote(("localhost", 8080))
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, PathBuf::from("/usr/bin/cargo"));
+    assert_eq!(command.timeout, Duration::from_secs(30));
+    assert_eq!(command.remote.as_deref(), Some("localhost:8080"));
+}
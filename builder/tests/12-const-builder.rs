@@ -0,0 +1,47 @@
+// With `#[builder(const)]` the builder can be used to initialize `const` and
+// `static` items. Its setters are `const fn`s that take the builder by value,
+// and the build method is a `const fn` returning the struct directly. A
+// missing field makes the build method panic, which in a constant is reported
+// as a compile-time error.
+//
+// Since the setters overwrite fields at compile time, the field types must
+// not have destructors; `each` and `with` are not available.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Descriptor {
+    name: &'static str,
+    id: u32,
+    alias: Option<&'static str>,
+}
+
+static DESCRIPTORS: [Descriptor; 2] = [
+    Descriptor::builder().name("read").id(0).build(),
+    Descriptor::builder()
+        .name("write")
+        .id(1)
+        .alias("put")
+        .build(),
+];
+
+const fn finish(id: Id) -> u32 {
+    id.id
+}
+
+#[derive(Builder)]
+#[builder(const, post_build = "finish", build_fn(name = "into_id", output = "u32"))]
+pub struct Id {
+    id: u32,
+}
+
+const ID: u32 = Id::builder().id(7).into_id();
+
+fn main() {
+    assert_eq!(DESCRIPTORS[0].name, "read");
+    assert!(DESCRIPTORS[0].alias.is_none());
+    assert_eq!(DESCRIPTORS[1].id, 1);
+    assert_eq!(DESCRIPTORS[1].alias, Some("put"));
+    assert_eq!(ID, 7);
+}
@@ -0,0 +1,17 @@
+// When `each` gives the one-at-a-time setter the same name as the field, that
+// setter replaces the field's ordinary setter, so there is no setter left to
+// take the arguments of a `with` closure. Rather than silently dropping the
+// closure, the macro reports the conflict. This is a compile_fail test.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "env", with = |pairs: Vec<(&str, &str)>| -> Vec<String> {
+        pairs.iter().map(|(k, v)| format!("{}={}", k, v)).collect()
+    })]
+    env: Vec<String>,
+}
+
+fn main() {}
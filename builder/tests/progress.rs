@@ -10,4 +10,8 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-build-fn.rs");
+    t.pass("tests/11-with-closure.rs");
+    t.pass("tests/12-const-builder.rs");
+    t.compile_fail("tests/13-with-each-conflict.rs");
 }
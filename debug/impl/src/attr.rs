@@ -0,0 +1,648 @@
+use proc_macro2::TokenTree;
+use quote::ToTokens;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    parse_quote, Attribute, Error, Expr, ExprLit, Field, Fields, Ident, Lit, LitInt, LitStr, Meta,
+    Path, Result, Token, Variant, WherePredicate,
+};
+
+use crate::format;
+
+/// Options given by `#[debug(...)]` attributes on the struct or enum itself.
+pub(crate) struct ContainerAttrs {
+    /// `bound = "..."`: replaces all inferred bounds.
+    pub bound: Option<Vec<WherePredicate>>,
+    /// `#[debug("...", args...)]`: template replacing the whole output.
+    pub format: Option<Format>,
+    /// `rename = "..."`: name printed in place of the type's identifier.
+    pub rename: Option<String>,
+    /// `max_items = N`: default limit for fields that are collections.
+    pub max_items: Option<usize>,
+    /// `max_len = N`: default limit for fields that are strings.
+    pub max_len: Option<usize>,
+    /// `transparent`: prints a single-field struct as its field.
+    pub transparent: bool,
+    /// `diff`: also generates a `debug_diff` method listing differing fields.
+    pub diff: bool,
+    /// `fields`: also generates a `FIELD_NAMES` const and a `debug_fields`
+    /// method giving the printed fields as key/value pairs.
+    pub fields: bool,
+    /// `max_depth = N`: prints values nested more than `N` levels below this
+    /// one as `...`.
+    pub max_depth: Option<usize>,
+    /// `unsafe(union_as = "field")`: prints a union as the given field, rather
+    /// than as an opaque `Name { .. }`.
+    pub union_as: Option<Ident>,
+}
+
+/// Options given by `#[debug(...)]` attributes on an enum variant.
+pub(crate) struct VariantAttrs {
+    /// `#[debug("...", args...)]`: template replacing the variant's output.
+    pub format: Option<Format>,
+    /// `rename = "..."`: name printed in place of the variant's identifier.
+    pub rename: Option<String>,
+}
+
+/// Options given by `#[display(...)]` attributes on a struct, enum or
+/// variant.
+pub(crate) struct DisplayAttrs {
+    /// `#[display("...", args...)]`: template giving the output.
+    pub format: Option<Format>,
+    /// `bound = "..."`: replaces all inferred bounds.
+    pub bound: Option<Vec<WherePredicate>>,
+}
+
+/// Options given by `#[debug...]` attributes on a field.
+pub(crate) struct FieldAttrs {
+    /// `#[debug = "..."]`, `fmt = "..."` or `#[debug("...", args...)]`: format
+    /// string applied to the field. `hex` and `bin` are shorthands for `{:#x}`
+    /// and `{:#b}`.
+    pub format: Option<Format>,
+    /// `bound = "..."`: replaces the bounds inferred from this field's type.
+    pub bound: Option<Vec<WherePredicate>>,
+    /// `skip`: leaves the field out of the output.
+    pub skip: bool,
+    /// `redact`, `redact = "..."` or `redact_len`: hides the field's value.
+    pub redact: Option<Redact>,
+    /// `with = "path"`: function formatting the field in place of its `Debug`
+    /// impl. `bytes = "hex"` and `bytes = "utf8_lossy"` are shorthands for
+    /// the functions printing byte buffers provided by derive_debug.
+    pub with: Option<Path>,
+    /// `rename = "..."`: name printed in place of the field's identifier.
+    pub rename: Option<String>,
+    /// `skip_if = "path"`: predicate called with the field at runtime, leaving
+    /// the field out of the output when it returns true.
+    pub skip_if: Option<Path>,
+    /// `max_items = N`: number of items of a collection printed.
+    pub max_items: Option<usize>,
+    /// `max_len = N`: number of characters of a string printed.
+    pub max_len: Option<usize>,
+}
+
+/// A format string followed by its arguments, like `"{}/{}", self.num,
+/// self.den`.
+pub(crate) struct Format {
+    pub pattern: LitStr,
+    /// Arguments of the format string. On a field, no arguments means the
+    /// field itself is the only argument.
+    pub args: Vec<Expr>,
+}
+
+/// Arguments passed to a format string that lists none of its own.
+#[derive(Clone, Copy)]
+pub(crate) enum Implicit {
+    /// No arguments, for a struct or variant with named fields.
+    Nothing,
+    /// The field itself, for a field's format.
+    Field,
+    /// The leading fields of a tuple struct or variant with this many fields,
+    /// as many as the placeholders use.
+    Tuple(usize),
+}
+
+impl Implicit {
+    /// The arguments implicitly passed to the template of a struct or
+    /// variant with `fields`.
+    pub fn of(fields: &Fields) -> Self {
+        match fields {
+            Fields::Unnamed(fields) => Implicit::Tuple(fields.unnamed.len()),
+            Fields::Named(_) | Fields::Unit => Implicit::Nothing,
+        }
+    }
+}
+
+/// A field option deciding whether or how the field's value is printed. Of
+/// these, a field may only have `skip_if` alongside one of the others, other
+/// than `skip`, since the rest would override each other.
+#[derive(Clone, Copy, PartialEq)]
+enum Shown {
+    Skip,
+    SkipIf,
+    Redact,
+    With,
+    Format,
+    MaxItems,
+    MaxLen,
+}
+
+impl Shown {
+    fn label(self) -> &'static str {
+        match self {
+            Shown::Skip => "`skip`",
+            Shown::SkipIf => "`skip_if`",
+            Shown::Redact => "`redact`",
+            Shown::With => "`with`",
+            Shown::Format => "a format",
+            Shown::MaxItems => "`max_items`",
+            Shown::MaxLen => "`max_len`",
+        }
+    }
+}
+
+/// How a `#[debug(redact)]` field is printed in place of its value.
+pub(crate) enum Redact {
+    /// Prints the given mask, `<redacted>` by default.
+    Mask(String),
+    /// Prints only the value's length.
+    Len,
+}
+
+impl ContainerAttrs {
+    /// Parses the attributes of a struct with `fields`, or of an enum or
+    /// union with `Fields::Unit`.
+    pub fn parse(attrs: &[Attribute], fields: &Fields) -> Result<Self> {
+        let mut container = ContainerAttrs {
+            bound: None,
+            format: None,
+            rename: None,
+            max_items: None,
+            max_len: None,
+            transparent: false,
+            diff: false,
+            fields: false,
+            max_depth: None,
+            union_as: None,
+        };
+        for attr in attrs {
+            if !attr.path().is_ident("debug") {
+                continue;
+            }
+            if let Some(format) = Format::from_attr(attr)? {
+                if container.format.is_some() {
+                    return Err(Error::new_spanned(attr, "duplicate `#[debug]` format"));
+                }
+                format.check(Implicit::of(fields))?;
+                container.format = Some(format);
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    if container.bound.is_some() {
+                        return Err(meta.error("duplicate `bound`"));
+                    }
+                    container.bound = Some(parse_bound(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    if container.rename.is_some() {
+                        return Err(meta.error("duplicate `rename`"));
+                    }
+                    container.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else if meta.path.is_ident("max_items") {
+                    if container.max_items.is_some() {
+                        return Err(meta.error("duplicate `max_items`"));
+                    }
+                    container.max_items = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("max_len") {
+                    if container.max_len.is_some() {
+                        return Err(meta.error("duplicate `max_len`"));
+                    }
+                    container.max_len = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("transparent") {
+                    container.transparent = true;
+                    Ok(())
+                } else if meta.path.is_ident("diff") {
+                    container.diff = true;
+                    Ok(())
+                } else if meta.path.is_ident("fields") {
+                    container.fields = true;
+                    Ok(())
+                } else if meta.path.is_ident("max_depth") {
+                    if container.max_depth.is_some() {
+                        return Err(meta.error("duplicate `max_depth`"));
+                    }
+                    container.max_depth = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("unsafe") {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("union_as") {
+                            if container.union_as.is_some() {
+                                return Err(meta.error("duplicate `union_as`"));
+                            }
+                            container.union_as = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+                            Ok(())
+                        } else {
+                            Err(meta.error("expected `union_as = \"...\"`"))
+                        }
+                    })
+                } else if meta.path.is_ident("union_as") {
+                    // Reading a union field is only sound if it was the last
+                    // one written, which the generated impl cannot check.
+                    Err(meta.error(
+                        "`union_as` reads a union field regardless of which one is \
+                         initialized; write `unsafe(union_as = \"...\")` to enable it",
+                    ))
+                } else {
+                    Err(meta.error(
+                        "expected `bound = \"...\"`, `rename = \"...\"`, `max_items = N`, \
+                         `max_len = N`, `max_depth = N`, `transparent`, `diff`, `fields`, \
+                         `unsafe(union_as = \"...\")` or a format string",
+                    ))
+                }
+            })?;
+        }
+        Ok(container)
+    }
+}
+
+impl VariantAttrs {
+    pub fn parse(variant: &Variant) -> Result<Self> {
+        let fields = &variant.fields;
+        let mut variant_attrs = VariantAttrs {
+            format: None,
+            rename: None,
+        };
+        for attr in &variant.attrs {
+            if !attr.path().is_ident("debug") {
+                continue;
+            }
+            if let Some(format) = Format::from_attr(attr)? {
+                if variant_attrs.format.is_some() {
+                    return Err(Error::new_spanned(attr, "duplicate `#[debug]` format"));
+                }
+                format.check(Implicit::of(fields))?;
+                variant_attrs.format = Some(format);
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if variant_attrs.rename.is_some() {
+                        return Err(meta.error("duplicate `rename`"));
+                    }
+                    variant_attrs.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename = \"...\"` or a format string"))
+                }
+            })?;
+        }
+        Ok(variant_attrs)
+    }
+}
+
+impl DisplayAttrs {
+    /// Parses the attributes of a struct or variant with `fields`, or of an
+    /// enum with `Fields::Unit`.
+    pub fn parse(attrs: &[Attribute], fields: &Fields) -> Result<Self> {
+        let mut display = DisplayAttrs {
+            format: None,
+            bound: None,
+        };
+        for attr in attrs {
+            if !attr.path().is_ident("display") {
+                continue;
+            }
+            if let Some(format) = Format::from_attr(attr)? {
+                if display.format.is_some() {
+                    return Err(Error::new_spanned(attr, "duplicate `#[display]` format"));
+                }
+                format.check(Implicit::of(fields))?;
+                display.format = Some(format);
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    if display.bound.is_some() {
+                        return Err(meta.error("duplicate `bound`"));
+                    }
+                    display.bound = Some(parse_bound(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `bound = \"...\"` or a format string"))
+                }
+            })?;
+        }
+        Ok(display)
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(field: &Field) -> Result<Self> {
+        let mut field_attrs = FieldAttrs {
+            format: None,
+            bound: None,
+            skip: false,
+            redact: None,
+            with: None,
+            rename: None,
+            skip_if: None,
+            max_items: None,
+            max_len: None,
+        };
+        for attr in &field.attrs {
+            if !attr.path().is_ident("debug") {
+                continue;
+            }
+            if let Some(format) = Format::from_attr(attr)? {
+                if field_attrs.format.is_some() {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "duplicate `#[debug]` format for this field",
+                    ));
+                }
+                field_attrs
+                    .check_compatible(Shown::Format)
+                    .map_err(|msg| Error::new_spanned(attr, msg))?;
+                format.check(Implicit::Field)?;
+                field_attrs.format = Some(format);
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                let compatible = |option| {
+                    field_attrs
+                        .check_compatible(option)
+                        .map_err(|msg| meta.error(msg))
+                };
+                if meta.path.is_ident("fmt") {
+                    if field_attrs.format.is_some() {
+                        return Err(meta.error("duplicate `#[debug]` format for this field"));
+                    }
+                    compatible(Shown::Format)?;
+                    let format = Format {
+                        pattern: meta.value()?.parse()?,
+                        args: vec![],
+                    };
+                    format.check(Implicit::Field)?;
+                    field_attrs.format = Some(format);
+                    Ok(())
+                } else if meta.path.is_ident("hex") || meta.path.is_ident("bin") {
+                    if field_attrs.format.is_some() {
+                        return Err(meta.error("duplicate `#[debug]` format for this field"));
+                    }
+                    compatible(Shown::Format)?;
+                    let pattern = if meta.path.is_ident("hex") {
+                        "{:#x}"
+                    } else {
+                        "{:#b}"
+                    };
+                    field_attrs.format = Some(Format {
+                        pattern: LitStr::new(pattern, meta.path.span()),
+                        args: vec![],
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("bound") {
+                    if field_attrs.bound.is_some() {
+                        return Err(meta.error("duplicate `bound`"));
+                    }
+                    field_attrs.bound = Some(parse_bound(&meta.value()?.parse()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("skip") {
+                    if field_attrs.skip {
+                        return Err(meta.error("duplicate `skip`"));
+                    }
+                    compatible(Shown::Skip)?;
+                    field_attrs.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("redact") {
+                    if field_attrs.redact.is_some() {
+                        return Err(meta.error("duplicate `redact`"));
+                    }
+                    compatible(Shown::Redact)?;
+                    let mask = if meta.input.peek(Token![=]) {
+                        meta.value()?.parse::<LitStr>()?.value()
+                    } else {
+                        "<redacted>".to_owned()
+                    };
+                    field_attrs.redact = Some(Redact::Mask(mask));
+                    Ok(())
+                } else if meta.path.is_ident("redact_len") {
+                    if field_attrs.redact.is_some() {
+                        return Err(meta.error("duplicate `redact`"));
+                    }
+                    compatible(Shown::Redact)?;
+                    field_attrs.redact = Some(Redact::Len);
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    if field_attrs.with.is_some() {
+                        return Err(meta.error("duplicate `with`"));
+                    }
+                    compatible(Shown::With)?;
+                    field_attrs.with = Some(parse_path(meta.value()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("bytes") {
+                    if field_attrs.with.is_some() {
+                        return Err(meta.error("duplicate `with`"));
+                    }
+                    compatible(Shown::With)?;
+                    let style = meta.value()?.parse::<LitStr>()?;
+                    field_attrs.with = Some(match style.value().as_str() {
+                        "hex" => parse_quote!(::derive_debug::__private::bytes_hex),
+                        "utf8_lossy" => parse_quote!(::derive_debug::__private::bytes_utf8_lossy),
+                        _ => {
+                            return Err(Error::new(
+                                style.span(),
+                                "expected `bytes = \"hex\"` or `bytes = \"utf8_lossy\"`",
+                            ))
+                        }
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("skip_if") {
+                    if field_attrs.skip_if.is_some() {
+                        return Err(meta.error("duplicate `skip_if`"));
+                    }
+                    compatible(Shown::SkipIf)?;
+                    field_attrs.skip_if = Some(parse_path(meta.value()?)?);
+                    Ok(())
+                } else if meta.path.is_ident("max_items") {
+                    if field_attrs.max_items.is_some() {
+                        return Err(meta.error("duplicate `max_items`"));
+                    }
+                    compatible(Shown::MaxItems)?;
+                    field_attrs.max_items = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("max_len") {
+                    if field_attrs.max_len.is_some() {
+                        return Err(meta.error("duplicate `max_len`"));
+                    }
+                    compatible(Shown::MaxLen)?;
+                    field_attrs.max_len = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    if field_attrs.rename.is_some() {
+                        return Err(meta.error("duplicate `rename`"));
+                    }
+                    field_attrs.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    Ok(())
+                } else {
+                    Err(meta.error(
+                        "expected `fmt = \"...\"`, `bound = \"...\"`, `with = \"...\"`, \
+                         `rename = \"...\"`, `skip_if = \"...\"`, `bytes = \"...\"`, \
+                         `max_items = N`, `max_len = N`, `skip`, `redact`, `redact_len`, \
+                         `hex` or `bin`",
+                    ))
+                }
+            })?;
+        }
+        Ok(field_attrs)
+    }
+
+    /// Checks that `option` can be given alongside the options already given,
+    /// rather than one silently overriding the other.
+    fn check_compatible(&self, option: Shown) -> std::result::Result<(), String> {
+        let given = [
+            (Shown::Skip, self.skip),
+            (Shown::SkipIf, self.skip_if.is_some()),
+            (Shown::Redact, self.redact.is_some()),
+            (Shown::With, self.with.is_some()),
+            (Shown::Format, self.format.is_some()),
+            (Shown::MaxItems, self.max_items.is_some()),
+            (Shown::MaxLen, self.max_len.is_some()),
+        ];
+        let conflict = given.into_iter().find(|&(other, given)| {
+            given
+                && other != option
+                && match (option, other) {
+                    (Shown::SkipIf, other) | (other, Shown::SkipIf) => other == Shown::Skip,
+                    _ => true,
+                }
+        });
+        match conflict {
+            Some((other, _)) => Err(format!(
+                "{} cannot be combined with {}",
+                option.label(),
+                other.label(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the field's value is hidden or printed in place of its `Debug`
+    /// output, by `redact`, `with` or a format string.
+    pub fn replaces_debug(&self) -> bool {
+        self.redact.is_some() || self.with.is_some() || self.format.is_some()
+    }
+
+    /// Whether the field's value is printed using its own `Debug` impl, and
+    /// so needs its type to be bounded.
+    pub fn uses_debug(&self) -> bool {
+        !self.skip
+            && self.redact.is_none()
+            && self.with.is_none()
+            && self
+                .format
+                .as_ref()
+                .is_none_or(|format| format.args.is_empty())
+    }
+}
+
+impl Format {
+    /// Names of the arguments passed explicitly as `name = value`.
+    pub fn explicit_names(&self) -> Vec<String> {
+        self.args
+            .iter()
+            .filter_map(|arg| match arg {
+                Expr::Assign(assign) => Some(assign.left.to_token_stream().to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Names the format string may refer to: those of its named placeholders,
+    /// and every identifier in its arguments.
+    pub fn mentioned_names(&self) -> Vec<String> {
+        fn idents(tokens: proc_macro2::TokenStream, names: &mut Vec<String>) {
+            for token in tokens {
+                match token {
+                    TokenTree::Ident(ident) => names.push(ident.unraw().to_string()),
+                    TokenTree::Group(group) => idents(group.stream(), names),
+                    TokenTree::Punct(_) | TokenTree::Literal(_) => {}
+                }
+            }
+        }
+        let arguments = format::parse(&self.pattern.value()).unwrap_or_default();
+        let mut names = format::named_arguments(&arguments)
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        for arg in &self.args {
+            idents(arg.to_token_stream(), &mut names);
+        }
+        names
+    }
+
+    /// Checks the format string and that its placeholders match its
+    /// arguments, or the `implicit` ones if it lists none.
+    fn check(&self, implicit: Implicit) -> Result<()> {
+        let error = |msg| Error::new(self.pattern.span(), msg);
+        let arguments = format::parse(&self.pattern.value()).map_err(error)?;
+        let named = self.explicit_names();
+        let positional = match implicit {
+            _ if self.args.len() > named.len() => self.args.len() - named.len(),
+            Implicit::Nothing => 0,
+            Implicit::Field => 1,
+            Implicit::Tuple(len) => format::positional_count(&arguments).min(len),
+        };
+        format::check_arguments(&arguments, positional, &named).map_err(error)
+    }
+
+    /// Parses `#[debug = "..."]` and `#[debug("...", args...)]`, or the same
+    /// forms of `#[display]`, returning `None` for the `#[debug(option, ...)]`
+    /// form.
+    fn from_attr(attr: &Attribute) -> Result<Option<Self>> {
+        match attr.meta {
+            Meta::NameValue(ref name_value) => match name_value.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ref pattern),
+                    ..
+                }) => Ok(Some(Format {
+                    pattern: pattern.clone(),
+                    args: vec![],
+                })),
+                ref value => Err(Error::new_spanned(value, "expected a format string")),
+            },
+            Meta::Path(ref path) => {
+                let name = path.to_token_stream();
+                Err(Error::new_spanned(
+                    attr,
+                    format!("expected `#[{0} = \"...\"]` or `#[{0}(...)]`", name),
+                ))
+            }
+            Meta::List(ref list) => {
+                let is_format = list.parse_args_with(|input: ParseStream| {
+                    let is_format = input.peek(LitStr);
+                    input.parse::<proc_macro2::TokenStream>()?;
+                    Ok(is_format)
+                })?;
+                if is_format {
+                    list.parse_args().map(Some)
+                } else {
+                    Ok(None)
+                }
+            }
+        }
+    }
+}
+
+impl Parse for Format {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let pattern = input.parse()?;
+        let mut args = vec![];
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            args.push(input.parse()?);
+        }
+        Ok(Format { pattern, args })
+    }
+}
+
+/// A path given either as a string literal or directly.
+fn parse_path(input: ParseStream) -> Result<Path> {
+    if input.peek(LitStr) {
+        input.parse::<LitStr>()?.parse()
+    } else {
+        input.parse()
+    }
+}
+
+fn parse_bound(lit: &LitStr) -> Result<Vec<WherePredicate>> {
+    let predicates = lit.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+    Ok(predicates.into_iter().collect())
+}
@@ -0,0 +1,246 @@
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::visit::{self, Visit};
+use syn::{
+    parse_quote, Data, DeriveInput, Expr, ExprUnary, Field, Fields, Generics, Ident, Result, Type,
+    TypePath, UnOp, WherePredicate,
+};
+
+use crate::attr::{ContainerAttrs, DisplayAttrs, FieldAttrs, Format};
+use crate::format::{self, Argument};
+
+/// Infers the bounds needed for the generated impl to compile: `Debug` for
+/// every type parameter mentioned in a field, and for every associated type
+/// path like `T::Value` rooted at a type parameter.
+///
+/// Type parameters mentioned only inside of `PhantomData` are left unbounded
+/// since `PhantomData<T>` is `Debug` for any `T`. A field with a
+/// `#[debug(bound = "...")]` attribute contributes those bounds instead, and
+/// skipped or redacted fields contribute none, as do the fields of a union
+/// other than its `union_as` field.
+pub(crate) fn infer_bounds(
+    generics: &Generics,
+    data: &Data,
+    container: &ContainerAttrs,
+) -> Result<Vec<WherePredicate>> {
+    let mut visitor = BoundVisitor {
+        params: generics.type_params().map(|param| &param.ident).collect(),
+        bounded: vec![],
+    };
+    let mut predicates = vec![];
+    let fields = match data {
+        Data::Union(data) => data
+            .fields
+            .named
+            .iter()
+            .filter(|field| field.ident.is_some() && field.ident == container.union_as)
+            .collect(),
+        _ => fields(data),
+    };
+    for field in fields {
+        let attrs = FieldAttrs::parse(field)?;
+        match attrs.bound {
+            Some(bound) => predicates.extend(bound),
+            None if attrs.uses_debug() => visitor.visit_type(&field.ty),
+            None => {}
+        }
+    }
+    predicates.extend(
+        visitor
+            .bounded
+            .into_iter()
+            .map(|ty| -> WherePredicate { parse_quote!(#ty: std::fmt::Debug) }),
+    );
+    Ok(predicates)
+}
+
+/// Infers the bounds of a `CustomDisplay` impl by the same heuristic, from the
+/// fields its templates format: `Display` for a field formatted by `{}`,
+/// `Debug` for one formatted by `{:?}`, and so on. Fields that no template
+/// formats contribute no bounds.
+pub(crate) fn infer_display_bounds(input: &DeriveInput) -> Result<Vec<WherePredicate>> {
+    let params = input
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect::<Vec<_>>();
+    let templates = match input.data {
+        Data::Struct(ref data) => vec![(&input.attrs, &data.fields, false)],
+        Data::Enum(ref data) => data
+            .variants
+            .iter()
+            .map(|variant| (&variant.attrs, &variant.fields, true))
+            .collect(),
+        Data::Union(_) => vec![],
+    };
+    let mut predicates = vec![];
+    for (attrs, fields, is_variant) in templates {
+        let format = match DisplayAttrs::parse(attrs, fields)?.format {
+            Some(format) => format,
+            None => continue,
+        };
+        for (field, ty) in formatted_fields(&format, fields, is_variant) {
+            let format_trait = match ty.as_str() {
+                "" => quote!(Display),
+                "?" | "x?" | "X?" => quote!(Debug),
+                "x" => quote!(LowerHex),
+                "X" => quote!(UpperHex),
+                "o" => quote!(Octal),
+                "b" => quote!(Binary),
+                "e" => quote!(LowerExp),
+                "E" => quote!(UpperExp),
+                // Fields are passed by reference, which `{:p}` formats as is.
+                _ => continue,
+            };
+            let mut visitor = BoundVisitor {
+                params: params.clone(),
+                bounded: vec![],
+            };
+            visitor.visit_type(&field.ty);
+            for ty in visitor.bounded {
+                let predicate: WherePredicate = parse_quote!(#ty: std::fmt::#format_trait);
+                if !predicates.contains(&predicate) {
+                    predicates.push(predicate);
+                }
+            }
+        }
+    }
+    Ok(predicates)
+}
+
+/// The fields formatted by the placeholders of a struct's or variant's
+/// template, with the type of each placeholder. A placeholder formats a field
+/// when it names the field, when it is passed an argument like `self.x` or, in
+/// a variant, `x`, or when it is passed the field implicitly as a tuple field.
+fn formatted_fields<'a>(
+    format: &Format,
+    fields: &'a Fields,
+    is_variant: bool,
+) -> Vec<(&'a Field, String)> {
+    let (named, positional): (Vec<_>, Vec<_>) = format
+        .args
+        .iter()
+        .partition(|arg| matches!(arg, Expr::Assign(_)));
+    format::formatted(&format.pattern.value())
+        .into_iter()
+        .filter_map(|(argument, ty)| {
+            let field = match argument {
+                Argument::Index(i) if positional.is_empty() => match fields {
+                    Fields::Unnamed(_) => fields.iter().nth(i),
+                    _ => None,
+                },
+                Argument::Index(i) => field_of(positional.get(i)?, fields, is_variant),
+                Argument::Name(name) => {
+                    let explicit = named.iter().find_map(|arg| match arg {
+                        Expr::Assign(assign)
+                            if assign.left.to_token_stream().to_string() == name =>
+                        {
+                            Some(&assign.right)
+                        }
+                        _ => None,
+                    });
+                    match explicit {
+                        Some(expr) => field_of(expr, fields, is_variant),
+                        None => field_named(&name, fields, is_variant),
+                    }
+                }
+            }?;
+            Some((field, ty))
+        })
+        .collect()
+}
+
+/// The field an argument of a template refers to, like `self.x`, `&self.0`
+/// or, in a variant, `x` or `*_0`.
+fn field_of<'a>(expr: &Expr, fields: &'a Fields, is_variant: bool) -> Option<&'a Field> {
+    match expr {
+        Expr::Reference(expr) => field_of(&expr.expr, fields, is_variant),
+        Expr::Paren(expr) => field_of(&expr.expr, fields, is_variant),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Deref(_),
+            expr,
+            ..
+        }) => field_of(expr, fields, is_variant),
+        Expr::Field(expr) if !is_variant => match *expr.base {
+            Expr::Path(ref base) if base.path.is_ident("self") => fields
+                .iter()
+                .zip(fields.members())
+                .find_map(|(field, member)| Some(field).filter(|_| member == expr.member)),
+            _ => None,
+        },
+        Expr::Path(path) if is_variant => field_named(
+            &path.path.get_ident()?.unraw().to_string(),
+            fields,
+            is_variant,
+        ),
+        _ => None,
+    }
+}
+
+/// The field named `name`. Inside a variant, a tuple field is named like `_0`.
+fn field_named<'a>(name: &str, fields: &'a Fields, is_variant: bool) -> Option<&'a Field> {
+    fields.iter().enumerate().find_map(|(i, field)| {
+        let matches = match field.ident {
+            Some(ref ident) => ident.unraw() == name,
+            None => is_variant && name == format!("_{}", i),
+        };
+        Some(field).filter(|_| matches)
+    })
+}
+
+/// All fields of a struct, or of every variant of an enum.
+fn fields(data: &Data) -> Vec<&Field> {
+    match data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .flat_map(|variant| variant.fields.iter())
+            .collect(),
+        Data::Union(data) => data.fields.named.iter().collect(),
+    }
+}
+
+struct BoundVisitor<'a> {
+    params: Vec<&'a Ident>,
+    bounded: Vec<Type>,
+}
+
+impl BoundVisitor<'_> {
+    fn is_param(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Path(ty) => ty.qself.is_none() && self.is_param_path(ty),
+            _ => false,
+        }
+    }
+
+    /// Whether the path is a type parameter `T` or starts with one, like
+    /// `T::Value`.
+    fn is_param_path(&self, ty: &TypePath) -> bool {
+        match ty.path.segments.first() {
+            Some(first) => ty.path.leading_colon.is_none() && self.params.contains(&&first.ident),
+            None => false,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for BoundVisitor<'_> {
+    fn visit_type_path(&mut self, ty: &'ast TypePath) {
+        let last = ty.path.segments.last();
+        if last.is_some_and(|segment| segment.ident == "PhantomData") {
+            return;
+        }
+        let rooted_at_param = match ty.qself {
+            Some(ref qself) => self.is_param(&qself.ty),
+            None => self.is_param_path(ty),
+        };
+        if rooted_at_param {
+            let ty = Type::Path(ty.clone());
+            if !self.bounded.contains(&ty) {
+                self.bounded.push(ty);
+            }
+            return;
+        }
+        visit::visit_type_path(self, ty);
+    }
+}
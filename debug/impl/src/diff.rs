@@ -0,0 +1,244 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Error, Field, Fields, Index, Result, WhereClause};
+
+use crate::attr::{ContainerAttrs, FieldAttrs, Format, VariantAttrs};
+use crate::{match_variants, shown_value, variant_bindings, variant_pattern};
+
+/// Generates the `debug_diff` method of a `#[debug(diff)]` type, along with
+/// its impl of `Diff` through which the types containing it recurse into it.
+///
+/// Fields are compared by their `Debug` output, or recursively if their type
+/// implements `Diff`. Fields printed through one of their options, like
+/// `redact` or a format string, are compared as printed, so that a diff never
+/// shows more than the `Debug` output does. Truncated fields are compared in
+/// full, since items past the limit are left out for brevity, not hidden.
+pub(crate) fn expand(
+    input: &DeriveInput,
+    container: &ContainerAttrs,
+    where_clause: &WhereClause,
+) -> Result<TokenStream> {
+    let ident = &input.ident;
+    let (body, shown) = match input.data {
+        Data::Struct(ref data) => {
+            let left = data
+                .fields
+                .members()
+                .map(|member| quote!(&self.#member))
+                .collect::<Vec<_>>();
+            let right = data
+                .fields
+                .members()
+                .map(|member| quote!(&other.#member))
+                .collect::<Vec<_>>();
+            if container.format.is_some() {
+                (whole_diff(), quote!(vec![]))
+            } else {
+                let fields = FieldDiffs::new(&data.fields, container)?;
+                (
+                    fields.body(&left, &right, container.transparent),
+                    fields.shown(&left),
+                )
+            }
+        }
+        Data::Enum(ref data) => {
+            let mut arms = vec![];
+            let mut shown_arms = vec![];
+            for variant in &data.variants {
+                let (left_pattern, left) = variant_pattern(variant, "self");
+                let (right_pattern, right) = variant_pattern(variant, "other");
+                let variant_attrs = VariantAttrs::parse(variant)?;
+                let (body, shown, bindings) =
+                    if container.format.is_some() || variant_attrs.format.is_some() {
+                        (whole_diff(), quote!(vec![]), quote!())
+                    } else {
+                        let fields = FieldDiffs::new(&variant.fields, container)?;
+                        (
+                            fields.body(&left, &right, false),
+                            fields.shown(&left),
+                            variant_bindings(variant, &left, fields.formats()),
+                        )
+                    };
+                arms.push(quote! {
+                    (#left_pattern, #right_pattern) => { #body }
+                });
+                shown_arms.push(quote! {
+                    #left_pattern => {
+                        #bindings
+                        #shown
+                    }
+                });
+            }
+            let body = if arms.is_empty() {
+                quote!(match *self {})
+            } else {
+                let whole = whole_diff();
+                quote! {
+                    match (self, other) {
+                        #(#arms)*
+                        #[allow(unreachable_patterns)]
+                        _ => #whole,
+                    }
+                }
+            };
+            (body, match_variants(&shown_arms))
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ident,
+                "`#[debug(diff)]` is not supported on unions",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Lists the fields whose value differs between `self` and
+            /// `other`, as printed by `Debug`.
+            pub fn debug_diff(&self, other: &Self) -> Vec<::derive_debug::FieldDiff> {
+                let mut diffs = Vec::new();
+                ::derive_debug::__private::Diff::diff_fields(self, other, "", &mut diffs);
+                diffs
+            }
+
+            #[doc(hidden)]
+            #[allow(clippy::useless_vec)]
+            fn __debug_diff_shown(&self) -> Vec<String> {
+                #shown
+            }
+        }
+
+        impl #impl_generics ::derive_debug::__private::Diff for #ident #ty_generics #where_clause {
+            fn diff_fields(
+                &self,
+                other: &Self,
+                path: &str,
+                diffs: &mut Vec<::derive_debug::FieldDiff>,
+            ) {
+                #[allow(unused_imports)]
+                use ::derive_debug::__private::{ViaDebug, ViaDiff};
+                #body
+            }
+        }
+    })
+}
+
+/// Compares `self` and `other` as a whole.
+fn whole_diff() -> TokenStream {
+    quote! {
+        ::derive_debug::__private::diff_debug(path, self, other, diffs)
+    }
+}
+
+/// The fields of a struct or variant, with their options.
+struct FieldDiffs<'a> {
+    fields: Vec<(&'a Field, FieldAttrs)>,
+    container: &'a ContainerAttrs,
+}
+
+impl<'a> FieldDiffs<'a> {
+    fn new(fields: &'a Fields, container: &'a ContainerAttrs) -> Result<Self> {
+        let fields = fields
+            .iter()
+            .map(|field| Ok((field, FieldAttrs::parse(field)?)))
+            .collect::<Result<_>>()?;
+        Ok(FieldDiffs { fields, container })
+    }
+
+    /// The format strings of the fields.
+    fn formats(&self) -> impl Iterator<Item = &Format> {
+        self.fields
+            .iter()
+            .filter_map(|(_, attrs)| attrs.format.as_ref())
+    }
+
+    /// Fields compared in a diff, with whether each is compared as its own
+    /// `Debug` output, so that it can be compared recursively.
+    fn compared(&self) -> impl Iterator<Item = (usize, &Field, &FieldAttrs, bool)> {
+        self.fields
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, attrs))| !attrs.skip)
+            .map(|(i, (field, attrs))| (i, *field, attrs, !attrs.replaces_debug()))
+    }
+
+    /// Statements comparing each field, where `left` and `right` are
+    /// expressions referencing each field of `self` and `other`. Fields that
+    /// are not plain are compared through the output of `__debug_diff_shown`.
+    /// A transparent struct's field is compared at the struct's own path.
+    fn body(&self, left: &[TokenStream], right: &[TokenStream], transparent: bool) -> TokenStream {
+        let mut shown = 0;
+        let compares = self.compared().map(|(i, field, attrs, plain)| {
+            let (left, right) = (&left[i], &right[i]);
+            let path = if transparent {
+                quote!(path)
+            } else {
+                let name = field_name(field, attrs, i);
+                quote!(&::derive_debug::__private::field_path(path, #name))
+            };
+            let compare = if plain {
+                quote! {
+                    (&::derive_debug::__private::DiffField(#left, #right)).diff_field(#path, diffs);
+                }
+            } else {
+                let index = Index::from(shown);
+                shown += 1;
+                quote! {
+                    ::derive_debug::__private::diff_strings(
+                        #path,
+                        &__left[#index],
+                        &__right[#index],
+                        diffs,
+                    );
+                }
+            };
+            match attrs.skip_if {
+                Some(ref predicate) => quote! {
+                    if !(#predicate(#left) && #predicate(#right)) {
+                        #compare
+                    }
+                },
+                None => compare,
+            }
+        });
+        let compares = compares.collect::<Vec<_>>();
+        let shown = if shown > 0 {
+            quote! {
+                let __left = self.__debug_diff_shown();
+                let __right = other.__debug_diff_shown();
+            }
+        } else {
+            quote!()
+        };
+        quote! {
+            #shown
+            #(#compares)*
+        }
+    }
+
+    /// The expression listing the printed output of each field that is not
+    /// plain, where `values` are expressions referencing each field of `self`.
+    fn shown(&self, values: &[TokenStream]) -> TokenStream {
+        let shown =
+            self.compared()
+                .filter(|(_, _, _, plain)| !plain)
+                .map(|(i, field, attrs, _)| {
+                    let shown = shown_value(field, attrs, self.container, &values[i]);
+                    quote!(format!("{:?}", #shown))
+                });
+        quote!(vec![#(#shown),*])
+    }
+}
+
+/// The name of a field in a diff's path.
+fn field_name(field: &Field, attrs: &FieldAttrs, index: usize) -> String {
+    match field.ident {
+        Some(ref ident) => attrs
+            .rename
+            .clone()
+            .unwrap_or_else(|| ident.unraw().to_string()),
+        None => index.to_string(),
+    }
+}
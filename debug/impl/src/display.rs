@@ -0,0 +1,99 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Error, Fields, Result};
+
+use crate::attr::DisplayAttrs;
+use crate::{
+    bound, match_variants, variant_bindings, variant_pattern, where_clause, write_template,
+};
+
+/// Generates a `Display` impl from `#[display("...")]` templates, given on a
+/// struct or on each variant of an enum. Unit variants without a template
+/// display their name. Templates refer to fields the same way as those of
+/// `CustomDebug`.
+pub(crate) fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let ident = &input.ident;
+    let container = match input.data {
+        Data::Struct(ref data) => DisplayAttrs::parse(&input.attrs, &data.fields)?,
+        _ => DisplayAttrs::parse(&input.attrs, &Fields::Unit)?,
+    };
+    let body = match input.data {
+        Data::Struct(ref data) => {
+            let format = container.format.as_ref().ok_or_else(|| {
+                Error::new_spanned(ident, "expected a `#[display(\"...\")]` template")
+            })?;
+            let values = data
+                .fields
+                .members()
+                .map(|member| quote!(&self.#member))
+                .collect::<Vec<_>>();
+            write_template(format, &data.fields, &values)
+        }
+        Data::Enum(ref data) => {
+            if let Some(ref format) = container.format {
+                return Err(Error::new_spanned(
+                    &format.pattern,
+                    "an enum's `#[display]` templates are given on each variant",
+                ));
+            }
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let variant_attrs = DisplayAttrs::parse(&variant.attrs, &variant.fields)?;
+                    if variant_attrs.bound.is_some() {
+                        return Err(Error::new_spanned(
+                            &variant.ident,
+                            "`bound` is given on the enum, not on a variant",
+                        ));
+                    }
+                    let (pattern, values) = variant_pattern(variant, "self");
+                    let body = match variant_attrs.format {
+                        Some(ref format) => write_template(format, &variant.fields, &values),
+                        None if variant.fields.is_empty() => {
+                            let name = variant.ident.unraw().to_string();
+                            quote!(__f.write_str(#name))
+                        }
+                        None => {
+                            return Err(Error::new_spanned(
+                                &variant.ident,
+                                "expected a `#[display(\"...\")]` template",
+                            ))
+                        }
+                    };
+                    let bindings = variant_bindings(variant, &values, &variant_attrs.format);
+                    Ok(quote! {
+                        #pattern => {
+                            #bindings
+                            #body
+                        }
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            match_variants(&arms)
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ident,
+                "`CustomDisplay` is not supported on unions",
+            ))
+        }
+    };
+
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let bounds = match container.bound {
+        Some(bound) => bound,
+        None => bound::infer_display_bounds(input)?,
+    };
+    let where_clause = where_clause(generics, bounds);
+
+    Ok(quote! {
+        impl #impl_generics std::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, __f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                #body
+            }
+        }
+    })
+}
@@ -0,0 +1,88 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{DeriveInput, Fields, Result, WhereClause};
+
+use crate::attr::{ContainerAttrs, FieldAttrs};
+use crate::{is_plain, shown_value};
+
+/// Generates the `FIELD_NAMES` const and `debug_fields` method of a
+/// `#[debug(fields)]` struct, giving the fields printed by its `Debug` impl as
+/// key/value pairs. Structs printed through a `#[debug("...")]` template have
+/// no such fields, so do not support the option.
+///
+/// A field printed through one of its options is returned as a view of the
+/// whole struct whose `Debug` impl prints only that field, so that options
+/// referencing other fields through `self` keep working.
+pub(crate) fn expand(
+    input: &DeriveInput,
+    fields: &Fields,
+    container: &ContainerAttrs,
+    where_clause: &WhereClause,
+) -> Result<TokenStream> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+
+    let mut names = vec![];
+    let mut entries = vec![];
+    let mut field_impls = vec![];
+    for ((i, field), member) in fields.iter().enumerate().zip(fields.members()) {
+        let attrs = FieldAttrs::parse(field)?;
+        if attrs.skip {
+            continue;
+        }
+        let name = match field.ident {
+            Some(ref ident) => attrs
+                .rename
+                .clone()
+                .unwrap_or_else(|| ident.unraw().to_string()),
+            None => i.to_string(),
+        };
+        let value = quote!(&self.#member);
+        let shown = if is_plain(field, &attrs, container) {
+            quote!(#value)
+        } else {
+            let shown = shown_value(field, &attrs, container, &value);
+            field_impls.push(quote! {
+                impl #impl_generics ::derive_debug::__private::DebugField<#i>
+                    for #ident #ty_generics #where_clause
+                {
+                    fn fmt_field(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        std::fmt::Debug::fmt(#shown, f)
+                    }
+                }
+            });
+            quote!(::derive_debug::__private::Field::<Self, #i>::new(self))
+        };
+        entries.push(match attrs.skip_if {
+            Some(predicate) => quote! {
+                if #predicate(#value) {
+                    None
+                } else {
+                    Some((#name, #shown as &dyn std::fmt::Debug))
+                }
+            },
+            None => quote!(Some((#name, #shown as &dyn std::fmt::Debug))),
+        });
+        names.push(name);
+    }
+
+    let len = entries.len();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Names of the fields printed by `Debug`, in order.
+            pub const FIELD_NAMES: &'static [&'static str] = &[#(#names),*];
+
+            /// The fields printed by `Debug`, each with its name and a value
+            /// printing it as it appears in the output.
+            pub fn debug_fields(
+                &self,
+            ) -> impl Iterator<Item = (&'static str, &dyn std::fmt::Debug)> + '_ {
+                let fields: [Option<(&'static str, &dyn std::fmt::Debug)>; #len] = [#(#entries),*];
+                fields.into_iter().flatten()
+            }
+        }
+
+        #(#field_impls)*
+    })
+}
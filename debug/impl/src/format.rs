@@ -0,0 +1,222 @@
+/// An argument referenced by a format string, either by a placeholder or by
+/// a `width$`/`.precision$` parameter.
+#[derive(Clone, PartialEq)]
+pub(crate) enum Argument {
+    /// A positional argument, explicit like `{1}` or implicit like `{}`.
+    Index(usize),
+    /// A named argument like `{x}`.
+    Name(String),
+}
+
+/// Parses a format string the way `format_args!` does, returning the
+/// arguments it references, or a description of what is wrong with it.
+pub(crate) fn parse(pattern: &str) -> Result<Vec<Argument>, String> {
+    parse_placeholders(pattern).map(|(arguments, _)| arguments)
+}
+
+/// The arguments formatted by the placeholders of a valid format string, each
+/// with the format trait formatting it: `""` for `Display`, or the type of a
+/// placeholder like `{:?}` or `{:x}`.
+pub(crate) fn formatted(pattern: &str) -> Vec<(Argument, String)> {
+    parse_placeholders(pattern)
+        .map(|(_, formatted)| formatted)
+        .unwrap_or_default()
+}
+
+/// Parses a format string, returning the arguments it references, and those
+/// formatted by placeholders along with the placeholders' types.
+#[allow(clippy::type_complexity)]
+fn parse_placeholders(pattern: &str) -> Result<(Vec<Argument>, Vec<(Argument, String)>), String> {
+    let mut arguments = vec![];
+    let mut formatted = vec![];
+    let mut next = 0;
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '}' => return Err("unmatched `}` in format string".to_owned()),
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err("unterminated `{` in format string".to_owned()),
+                    }
+                }
+                let (argument, spec) = match placeholder.split_once(':') {
+                    Some((argument, spec)) => (argument, spec),
+                    None => (placeholder.as_str(), ""),
+                };
+                // A `.*` precision takes the next positional argument before
+                // the value itself does.
+                let ty = parse_spec(spec, &mut arguments, &mut next)?;
+                let argument = match parse_argument(argument) {
+                    Some(argument) => argument,
+                    None if argument.is_empty() => {
+                        next += 1;
+                        Argument::Index(next - 1)
+                    }
+                    None => {
+                        return Err(format!("invalid argument `{}` in format string", argument))
+                    }
+                };
+                formatted.push((argument.clone(), ty.to_owned()));
+                arguments.push(argument);
+            }
+            _ => {}
+        }
+    }
+    Ok((arguments, formatted))
+}
+
+/// Names of the named arguments referenced by a format string, like `x` and
+/// `y` in `"Point({x}, {y:?})"`, in order of first use.
+pub(crate) fn named_arguments(arguments: &[Argument]) -> Vec<&str> {
+    let mut names = vec![];
+    for argument in arguments {
+        if let Argument::Name(name) = argument {
+            if !names.contains(&name.as_str()) {
+                names.push(name.as_str());
+            }
+        }
+    }
+    names
+}
+
+/// The number of positional arguments the placeholders refer to.
+pub(crate) fn positional_count(arguments: &[Argument]) -> usize {
+    arguments
+        .iter()
+        .filter_map(|argument| match argument {
+            Argument::Index(i) => Some(i + 1),
+            Argument::Name(_) => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Checks that the referenced arguments match the `positional` arguments
+/// and the explicitly `named` arguments that are passed.
+pub(crate) fn check_arguments(
+    arguments: &[Argument],
+    positional: usize,
+    named: &[String],
+) -> Result<(), String> {
+    let expected = positional_count(arguments);
+    if expected != positional {
+        return Err(format!(
+            "format string expects {} {}, but {} {} given",
+            expected,
+            if expected == 1 {
+                "argument"
+            } else {
+                "arguments"
+            },
+            positional,
+            if positional == 1 { "is" } else { "are" },
+        ));
+    }
+    if let Some(unused) = (0..positional).find(|i| !arguments.contains(&Argument::Index(*i))) {
+        return Err(format!(
+            "argument {} is never used by the format string",
+            unused
+        ));
+    }
+    if let Some(unused) = named
+        .iter()
+        .find(|name| !arguments.contains(&Argument::Name(name.to_string())))
+    {
+        return Err(format!(
+            "named argument `{}` is never used by the format string",
+            unused
+        ));
+    }
+    Ok(())
+}
+
+/// An explicit argument: an index like `1` or a name like `x`.
+fn parse_argument(argument: &str) -> Option<Argument> {
+    if !argument.is_empty() && argument.chars().all(|c| c.is_ascii_digit()) {
+        return argument.parse().ok().map(Argument::Index);
+    }
+    let mut chars = argument.chars();
+    let first = chars.next()?;
+    if (first.is_alphabetic() || first == '_') && chars.all(|c| c.is_alphanumeric() || c == '_') {
+        Some(Argument::Name(argument.to_owned()))
+    } else {
+        None
+    }
+}
+
+/// Parses `[[fill]align][sign]['#']['0'][width]['.' precision][type]`,
+/// recording the arguments referenced by the width and precision, and
+/// returning the type.
+fn parse_spec<'a>(
+    spec: &'a str,
+    arguments: &mut Vec<Argument>,
+    next: &mut usize,
+) -> Result<&'a str, String> {
+    let is_align = |c: Option<char>| matches!(c, Some('<' | '^' | '>'));
+    let mut rest = spec;
+    if is_align(rest.chars().nth(1)) {
+        rest = &rest[rest.chars().next().unwrap().len_utf8() + 1..];
+    } else if is_align(rest.chars().next()) {
+        rest = &rest[1..];
+    }
+    rest = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+    rest = rest.strip_prefix('#').unwrap_or(rest);
+    if rest.starts_with('0') && !rest[1..].starts_with('$') {
+        rest = &rest[1..];
+    }
+    if let Some((width, after)) = parse_count(rest) {
+        if let Some(argument) = width {
+            arguments.push(argument);
+        }
+        rest = after;
+    }
+    if let Some(after) = rest.strip_prefix('.') {
+        if let Some(after) = after.strip_prefix('*') {
+            arguments.push(Argument::Index(*next));
+            *next += 1;
+            rest = after;
+        } else {
+            match parse_count(after) {
+                Some((precision, after)) => {
+                    if let Some(argument) = precision {
+                        arguments.push(argument);
+                    }
+                    rest = after;
+                }
+                None => return Err("expected a precision after `.` in format string".to_owned()),
+            }
+        }
+    }
+    match rest {
+        "" | "?" | "x?" | "X?" | "x" | "X" | "o" | "b" | "e" | "E" | "p" => Ok(rest),
+        _ => Err(format!("unknown format trait `{}`", rest)),
+    }
+}
+
+/// Parses a width or precision: either an integer, or an argument followed by
+/// `$`. Returns the referenced argument, if any, and the remaining spec.
+fn parse_count(spec: &str) -> Option<(Option<Argument>, &str)> {
+    let end = spec
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(spec.len());
+    let (count, rest) = spec.split_at(end);
+    if let Some(rest) = rest.strip_prefix('$') {
+        return Some((Some(parse_argument(count)?), rest));
+    }
+    let digits = count.len() - count.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        None
+    } else {
+        Some((None, &spec[digits..]))
+    }
+}
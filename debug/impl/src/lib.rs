@@ -0,0 +1,508 @@
+mod attr;
+mod bound;
+mod diff;
+mod display;
+mod fields;
+mod format;
+mod truncate;
+
+use proc_macro::TokenStream;
+
+use proc_macro2::TokenTree;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{
+    parse_macro_input, parse_quote, Data, DataUnion, DeriveInput, Error, Field, Fields, Generics,
+    Ident, Meta, Path, Variant, WhereClause, WherePredicate,
+};
+
+use crate::attr::{ContainerAttrs, FieldAttrs, Format, Redact, VariantAttrs};
+
+#[proc_macro_derive(CustomDebug, attributes(debug))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    display::expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_ident = &input.ident;
+
+    let container = match input.data {
+        Data::Struct(ref data) => ContainerAttrs::parse(&input.attrs, &data.fields)?,
+        _ => ContainerAttrs::parse(&input.attrs, &Fields::Unit)?,
+    };
+    let struct_name = container
+        .rename
+        .clone()
+        .unwrap_or_else(|| struct_ident.unraw().to_string());
+    match (&input.data, &container.union_as) {
+        (Data::Union(_), _) | (_, None) => {}
+        (_, Some(union_as)) => {
+            return Err(Error::new_spanned(
+                union_as,
+                "`union_as` is only supported on unions",
+            ));
+        }
+    }
+    let body = match &input.data {
+        Data::Struct(ref data) => {
+            let values = data
+                .fields
+                .members()
+                .map(|member| quote!(&self.#member))
+                .collect::<Vec<_>>();
+            if container.transparent {
+                debug_transparent(&input.ident, &data.fields, &values, &container)?
+            } else {
+                match container.format {
+                    Some(ref format) => write_template(format, &data.fields, &values),
+                    None => debug_fields(&struct_name, &data.fields, &values, &container)?,
+                }
+            }
+        }
+        Data::Enum(_) | Data::Union(_) if container.transparent => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`#[debug(transparent)]` is only supported on structs",
+            ));
+        }
+        Data::Enum(ref data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let variant_ident = &variant.ident;
+                    let (pattern, values) = variant_pattern(variant, "self");
+                    let variant_attrs = VariantAttrs::parse(variant)?;
+                    let field_attrs = variant
+                        .fields
+                        .iter()
+                        .map(FieldAttrs::parse)
+                        .collect::<syn::Result<Vec<_>>>()?;
+                    let bindings = variant_bindings(
+                        variant,
+                        &values,
+                        variant_attrs
+                            .format
+                            .iter()
+                            .chain(field_attrs.iter().filter_map(|attrs| attrs.format.as_ref())),
+                    );
+                    let variant_name = variant_attrs
+                        .rename
+                        .unwrap_or_else(|| variant_ident.unraw().to_string());
+                    let body = match variant_attrs.format {
+                        Some(ref format) => write_template(format, &variant.fields, &values),
+                        None => debug_fields(&variant_name, &variant.fields, &values, &container)?,
+                    };
+                    Ok(quote! {
+                        #pattern => {
+                            #bindings
+                            #body
+                        }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            match_variants(&arms)
+        }
+        Data::Union(ref data) => match container.format {
+            Some(ref format) => write_template(format, &Fields::Unit, &[]),
+            None => debug_union(&struct_name, data, &container)?,
+        },
+    };
+
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let bounds = match container.bound {
+        Some(ref bound) => bound.clone(),
+        None => bound::infer_bounds(generics, &input.data, &container)?,
+    };
+    let where_clause = where_clause(generics, bounds);
+    let diff = if container.diff {
+        diff::expand(input, &container, &where_clause)?
+    } else {
+        quote!()
+    };
+    let fields = match input.data {
+        _ if !container.fields => quote!(),
+        Data::Struct(ref data) if container.format.is_none() => {
+            fields::expand(input, &data.fields, &container, &where_clause)?
+        }
+        _ => {
+            return Err(Error::new_spanned(
+                struct_ident,
+                "`#[debug(fields)]` is only supported on structs printed field by field",
+            ));
+        }
+    };
+
+    // Every CustomDebug value counts as a level of nesting, whether or not its
+    // own type limits the depth.
+    let max_depth = match container.max_depth {
+        Some(max_depth) => quote!(Some(#max_depth)),
+        None => quote!(None),
+    };
+
+    Ok(quote! {
+        impl #impl_generics std::fmt::Debug for #struct_ident #ty_generics #where_clause{
+            fn fmt(&self, __f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let _depth = match ::derive_debug::__private::Depth::enter(#max_depth) {
+                    Some(depth) => depth,
+                    None => return __f.write_str("..."),
+                };
+                #body
+            }
+        }
+
+        #fields
+
+        #diff
+    })
+}
+
+/// Generates the formatting of `fields` under `name`, where `values` are
+/// expressions evaluating to a reference to each field.
+fn debug_fields(
+    name: &str,
+    fields: &Fields,
+    values: &[proc_macro2::TokenStream],
+    container: &ContainerAttrs,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let attrs = fields
+        .iter()
+        .map(FieldAttrs::parse)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let non_exhaustive = attrs.iter().any(|attrs| attrs.skip);
+    let mut skip_if = false;
+    let mut entries = vec![];
+    for ((field, value), attrs) in fields.iter().zip(values).zip(attrs) {
+        if attrs.skip {
+            continue;
+        }
+        let shown = shown_value(field, &attrs, container, value);
+        let entry = match field.ident {
+            Some(ref ident) => {
+                let name = attrs.rename.unwrap_or_else(|| ident.unraw().to_string());
+                quote!(__debug.field(#name, #shown);)
+            }
+            None => quote!(__debug.field(#shown);),
+        };
+        entries.push(match attrs.skip_if {
+            Some(predicate) if non_exhaustive => quote! {
+                if !#predicate(#value) {
+                    #entry
+                }
+            },
+            Some(predicate) => {
+                skip_if = true;
+                quote! {
+                    if #predicate(#value) {
+                        __non_exhaustive = true;
+                    } else {
+                        #entry
+                    }
+                }
+            }
+            None => entry,
+        });
+    }
+    let start = match fields {
+        Fields::Named(_) => quote!(__f.debug_struct(#name)),
+        Fields::Unnamed(_) => quote!(__f.debug_tuple(#name)),
+        Fields::Unit => return Ok(quote!(__f.write_str(#name))),
+    };
+    // Fields left out by `skip_if` are only known at runtime, so whether the
+    // output is marked non-exhaustive is too.
+    let (flag, finish) = if non_exhaustive {
+        (quote!(), quote!(__debug.finish_non_exhaustive()))
+    } else if skip_if {
+        (
+            quote!(let mut __non_exhaustive = false;),
+            quote! {
+                if __non_exhaustive {
+                    __debug.finish_non_exhaustive()
+                } else {
+                    __debug.finish()
+                }
+            },
+        )
+    } else {
+        (quote!(), quote!(__debug.finish()))
+    };
+    Ok(quote! {{
+        let mut __debug = #start;
+        #flag
+        #(#entries)*
+        #finish
+    }})
+}
+
+/// Generates the output of a `#[debug(transparent)]` struct, which is that of
+/// its only field, alternate mode and all.
+fn debug_transparent(
+    ident: &Ident,
+    fields: &Fields,
+    values: &[proc_macro2::TokenStream],
+    container: &ContainerAttrs,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if fields.len() != 1 {
+        return Err(Error::new_spanned(
+            ident,
+            "`#[debug(transparent)]` requires a struct with exactly one field",
+        ));
+    }
+    let field = fields.iter().next().unwrap();
+    let attrs = FieldAttrs::parse(field)?;
+    if attrs.skip || attrs.skip_if.is_some() {
+        // The struct prints nothing but its field, so there is no output left
+        // to print in place of a hidden one.
+        let is_skip = |token| match token {
+            TokenTree::Ident(ident) => ident == "skip" || ident == "skip_if",
+            _ => false,
+        };
+        let attr = field
+            .attrs
+            .iter()
+            .find(|attr| match attr.meta {
+                Meta::List(ref list) if attr.path().is_ident("debug") => {
+                    list.tokens.clone().into_iter().any(is_skip)
+                }
+                _ => false,
+            })
+            .unwrap();
+        return Err(Error::new_spanned(
+            attr,
+            "`skip` and `skip_if` are not supported on the field of a \
+             `#[debug(transparent)]` struct",
+        ));
+    }
+    let shown = shown_value(field, &attrs, container, &values[0]);
+    Ok(quote! {
+        std::fmt::Debug::fmt(#shown, __f)
+    })
+}
+
+/// Generates the formatting of a union: opaque, like `Name { .. }`, since which
+/// field is initialized is unknown, or as the field chosen by
+/// `#[debug(unsafe(union_as = "..."))]`.
+fn debug_union(
+    name: &str,
+    data: &DataUnion,
+    container: &ContainerAttrs,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = match container.union_as {
+        Some(ref ident) => ident,
+        None => return Ok(quote!(__f.debug_struct(#name).finish_non_exhaustive())),
+    };
+    let field = data
+        .fields
+        .named
+        .iter()
+        .find(|field| field.ident.as_ref() == Some(ident))
+        .ok_or_else(|| {
+            Error::new_spanned(ident, format!("union has no field named `{}`", ident))
+        })?;
+    let attrs = FieldAttrs::parse(field)?;
+    let field_name = attrs
+        .rename
+        .clone()
+        .unwrap_or_else(|| ident.unraw().to_string());
+    let shown = shown_value(field, &attrs, container, &quote!(unsafe { &self.#ident }));
+    Ok(quote! {
+        __f.debug_struct(#name).field(#field_name, #shown).finish()
+    })
+}
+
+/// The expression printed for a field, given `value` referencing the field:
+/// the reference itself, or a value formatting it according to the field's
+/// options.
+fn shown_value(
+    field: &Field,
+    attrs: &FieldAttrs,
+    container: &ContainerAttrs,
+    value: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match attrs.redact {
+        Some(Redact::Mask(ref mask)) => return quote!(&format_args!("{}", #mask)),
+        Some(Redact::Len) => return quote!(&format_args!("<redacted len={}>", #value.len())),
+        None => {}
+    }
+    if let Some(ref with) = attrs.with {
+        return debug_with(with, value);
+    }
+    if let Some(Format {
+        ref pattern,
+        ref args,
+    }) = attrs.format
+    {
+        return if args.is_empty() {
+            quote!(&format_args!(#pattern, #value))
+        } else {
+            quote!(&format_args!(#pattern, #(#args),*))
+        };
+    }
+    truncate::truncated(field, attrs, container, value).unwrap_or_else(|| quote!(#value))
+}
+
+/// Whether a field is printed as its own `Debug` output, rather than through
+/// one of its options.
+fn is_plain(field: &Field, attrs: &FieldAttrs, container: &ContainerAttrs) -> bool {
+    !attrs.replaces_debug() && truncate::truncated(field, attrs, container, &quote!()).is_none()
+}
+
+/// The pattern matching `variant` in a `match self`, binding each field to
+/// `__<prefix>_<index>`, and expressions referencing each field through those
+/// bindings.
+fn variant_pattern(
+    variant: &Variant,
+    prefix: &str,
+) -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
+    let variant_ident = &variant.ident;
+    let bindings = (0..variant.fields.len())
+        .map(|i| format_ident!("__{}_{}", prefix, i))
+        .collect::<Vec<_>>();
+    let pattern = match variant.fields {
+        Fields::Named(ref fields) => {
+            let idents = fields.named.iter().map(|field| &field.ident);
+            quote!(Self::#variant_ident { #(#idents: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(Self::#variant_ident( #(#bindings),* )),
+        Fields::Unit => quote!(Self::#variant_ident),
+    };
+    let values = bindings
+        .iter()
+        .map(|binding| quote!(#binding))
+        .collect::<Vec<_>>();
+    (pattern, values)
+}
+
+/// Binds the fields of a variant mentioned by `formats` to their own name, or
+/// to `_0`, `_1`, ... in a tuple variant, so that format arguments can refer to
+/// the variant's fields the way they refer to a struct's fields through `self`.
+///
+/// Fields that are not mentioned are left unbound, so that they do not hide
+/// functions of the same name called by `with` or `skip_if`.
+fn variant_bindings<'a>(
+    variant: &Variant,
+    values: &[proc_macro2::TokenStream],
+    formats: impl IntoIterator<Item = &'a Format>,
+) -> proc_macro2::TokenStream {
+    let mentioned = formats
+        .into_iter()
+        .flat_map(Format::mentioned_names)
+        .collect::<Vec<_>>();
+    let (names, values): (Vec<_>, Vec<_>) = variant
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match field.ident {
+            Some(ref ident) => (ident.clone(), &values[i]),
+            None => (format_ident!("_{}", i), &values[i]),
+        })
+        .filter(|(name, _)| mentioned.contains(&name.unraw().to_string()))
+        .unzip();
+    if names.is_empty() {
+        return quote!();
+    }
+    quote! {
+        #[allow(unused_variables)]
+        let (#(#names,)*) = (#(#values,)*);
+    }
+}
+
+/// A `match self` over the arms of every variant.
+fn match_variants(arms: &[proc_macro2::TokenStream]) -> proc_macro2::TokenStream {
+    if arms.is_empty() {
+        quote!(match *self {})
+    } else {
+        quote! {
+            match self {
+                #(#arms)*
+            }
+        }
+    }
+}
+
+/// The input's own where clause with `bounds` added.
+fn where_clause(generics: &Generics, bounds: Vec<WherePredicate>) -> WhereClause {
+    let mut where_clause = generics
+        .where_clause
+        .clone()
+        .unwrap_or_else(|| parse_quote!(where));
+    where_clause.predicates.extend(bounds);
+    where_clause
+}
+
+/// Generates the output of a `#[debug("...", args...)]` or
+/// `#[display("...", args...)]` template on a struct or variant. Named fields
+/// referenced by the template's placeholders, like `{x}`, are passed as named
+/// arguments. If the template lists no positional arguments, the fields of a
+/// tuple struct or variant are passed as the positional arguments, like `{0}`.
+fn write_template(
+    format: &Format,
+    fields: &Fields,
+    values: &[proc_macro2::TokenStream],
+) -> proc_macro2::TokenStream {
+    let Format { pattern, args } = format;
+    let explicit = format.explicit_names();
+    let arguments = format::parse(&pattern.value()).unwrap_or_default();
+    let named = format::named_arguments(&arguments)
+        .into_iter()
+        .filter(|name| !explicit.iter().any(|explicit| explicit == name))
+        .filter_map(|name| {
+            let (field, value) = fields
+                .iter()
+                .zip(values)
+                .find(|(field, _)| field.ident.as_ref().is_some_and(|ident| *ident == name))?;
+            let ident = &field.ident;
+            Some(quote!(#ident = #value))
+        });
+    let positional = match fields {
+        Fields::Unnamed(_) if args.len() == explicit.len() => {
+            &values[..format::positional_count(&arguments).min(values.len())]
+        }
+        _ => &[],
+    };
+    quote! {
+        __f.write_fmt(format_args!(#pattern #(, #positional)* #(, #args)* #(, #named)*))
+    }
+}
+
+/// Wraps a reference to a field in a value whose `Debug` impl calls the
+/// `#[debug(with = "...")]` function, so it can be passed to `DebugStruct`.
+fn debug_with(with: &Path, value: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    quote! {
+        &{
+            struct DebugWith<F>(F);
+
+            impl<F> std::fmt::Debug for DebugWith<F>
+            where
+                F: Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+            {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    (self.0)(f)
+                }
+            }
+
+            fn debug_with<F>(fmt: F) -> DebugWith<F>
+            where
+                F: Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+            {
+                DebugWith(fmt)
+            }
+
+            debug_with(move |f| #with(#value, f))
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Field, Type};
+
+use crate::attr::{ContainerAttrs, FieldAttrs};
+
+/// Wraps `value`, referencing the field, in the runtime's `MaxItems` or
+/// `MaxLen` if the field has a `max_items` or `max_len` limit, or in
+/// `MaxEntries` or `MaxSetItems` for a `max_items` limit on a field that looks
+/// like a map or a set.
+///
+/// A limit given on the struct or enum applies to the fields that look like
+/// collections or strings respectively, judging by their type's name, since
+/// other types would not support it.
+pub(crate) fn truncated(
+    field: &Field,
+    attrs: &FieldAttrs,
+    container: &ContainerAttrs,
+    value: &TokenStream,
+) -> Option<TokenStream> {
+    // Pass a reference-typed field as the reference itself, since a reference
+    // to a reference is neither iterable nor a string.
+    let value = match field.ty {
+        Type::Reference(_) => quote!(*#value),
+        _ => quote!(#value),
+    };
+    let max_items = attrs
+        .max_items
+        .or_else(|| container.max_items.filter(|_| is_collection(&field.ty)));
+    if let Some(max_items) = max_items {
+        return Some(if is_map(&field.ty) {
+            quote!(&::derive_debug::__private::MaxEntries(#value, #max_items))
+        } else if is_set(&field.ty) {
+            quote!(&::derive_debug::__private::MaxSetItems(#value, #max_items))
+        } else {
+            quote!(&::derive_debug::__private::MaxItems(#value, #max_items))
+        });
+    }
+    let max_len = attrs
+        .max_len
+        .or_else(|| container.max_len.filter(|_| is_string(&field.ty)));
+    if let Some(max_len) = max_len {
+        return Some(quote!(&::derive_debug::__private::MaxLen(#value, #max_len)));
+    }
+    None
+}
+
+fn is_collection(ty: &Type) -> bool {
+    const COLLECTIONS: &[&str] = &[
+        "Vec",
+        "VecDeque",
+        "LinkedList",
+        "BinaryHeap",
+        "HashMap",
+        "BTreeMap",
+        "HashSet",
+        "BTreeSet",
+    ];
+    match ty {
+        Type::Array(_) | Type::Slice(_) => true,
+        Type::Reference(ty) => is_collection(&ty.elem),
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| COLLECTIONS.iter().any(|name| segment.ident == name)),
+        _ => false,
+    }
+}
+
+fn is_map(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ty) => is_map(&ty.elem),
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "HashMap" || segment.ident == "BTreeMap"),
+        _ => false,
+    }
+}
+
+fn is_set(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ty) => is_set(&ty.elem),
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "HashSet" || segment.ident == "BTreeSet"),
+        _ => false,
+    }
+}
+
+fn is_string(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ty) => is_string(&ty.elem),
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "String" || segment.ident == "str"),
+        _ => false,
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt::{self, Debug, Write};
+
+/// Prints bytes for `#[debug(bytes = "hex")]`: as a hex string like
+/// `48656c6c6f`, or in alternate mode as a hexdump of 16 bytes per line, like
+/// `00000000  48 65 6c 6c 6f  |Hello|`.
+pub fn bytes_hex<T>(bytes: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result
+where
+    T: ?Sized + AsRef<[u8]>,
+{
+    let bytes = bytes.as_ref();
+    if !f.alternate() {
+        for byte in bytes {
+            write!(f, "{:02x}", byte)?;
+        }
+        return Ok(());
+    }
+    for (i, line) in bytes.chunks(16).enumerate() {
+        if i > 0 {
+            f.write_char('\n')?;
+        }
+        write!(f, "{:08x} ", i * 16)?;
+        for byte in line {
+            write!(f, " {:02x}", byte)?;
+        }
+        for _ in line.len()..16 {
+            f.write_str("   ")?;
+        }
+        f.write_str("  |")?;
+        for &byte in line {
+            let c = if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            };
+            f.write_char(c)?;
+        }
+        f.write_char('|')?;
+    }
+    Ok(())
+}
+
+/// Prints bytes for `#[debug(bytes = "utf8_lossy")]` as a string, replacing
+/// invalid UTF-8 with U+FFFD.
+pub fn bytes_utf8_lossy<T>(bytes: &T, f: &mut fmt::Formatter<'_>) -> fmt::Result
+where
+    T: ?Sized + AsRef<[u8]>,
+{
+    Debug::fmt(&String::from_utf8_lossy(bytes.as_ref()), f)
+}
@@ -0,0 +1,41 @@
+use std::cell::Cell;
+
+thread_local! {
+    // Number of CustomDebug values being printed on this thread, and the
+    // depth at which values are printed as `...` in place of their fields.
+    static DEPTH: Cell<(usize, usize)> = const { Cell::new((0, usize::MAX)) };
+}
+
+/// Marks a CustomDebug value as being printed until dropped, so that values
+/// nested in it are one level deeper.
+pub struct Depth {
+    previous: (usize, usize),
+}
+
+impl Depth {
+    /// Enters a value whose type has `#[debug(max_depth = N)]` if
+    /// `max_depth` is `Some(N)`. Returns `None` if the value is below the
+    /// maximum depth of a value it is nested in, so is printed as `...`.
+    pub fn enter(max_depth: Option<usize>) -> Option<Self> {
+        DEPTH.with(|cell| {
+            let (depth, limit) = cell.get();
+            if depth >= limit {
+                return None;
+            }
+            let nested_limit = match max_depth {
+                Some(max_depth) => limit.min(depth.saturating_add(max_depth)),
+                None => limit,
+            };
+            cell.set((depth + 1, nested_limit));
+            Some(Depth {
+                previous: (depth, limit),
+            })
+        })
+    }
+}
+
+impl Drop for Depth {
+    fn drop(&mut self) {
+        DEPTH.with(|cell| cell.set(self.previous));
+    }
+}
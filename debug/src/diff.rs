@@ -0,0 +1,100 @@
+use std::fmt::{self, Debug, Display};
+
+/// A field whose value differs between two values compared by the
+/// `debug_diff` method of `#[debug(diff)]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// Path to the field from the compared values, like `config.retries` or
+    /// `points.0`. Empty when the values differ as a whole, like two different
+    /// variants of an enum.
+    pub path: String,
+    /// The field's value on the left, as printed by `Debug`.
+    pub left: String,
+    /// The field's value on the right, as printed by `Debug`.
+    pub right: String,
+}
+
+impl Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        write!(f, "{} != {}", self.left, self.right)
+    }
+}
+
+/// Implemented by `#[debug(diff)]` types, so that the `debug_diff` of a type
+/// containing them recurses into their fields.
+pub trait Diff {
+    fn diff_fields(&self, other: &Self, path: &str, diffs: &mut Vec<FieldDiff>);
+}
+
+impl<T> Diff for &T
+where
+    T: ?Sized + Diff,
+{
+    fn diff_fields(&self, other: &Self, path: &str, diffs: &mut Vec<FieldDiff>) {
+        (**self).diff_fields(*other, path, diffs);
+    }
+}
+
+/// A field of two compared values. Through autoref specialization, calling
+/// `(&DiffField(left, right)).diff_field(...)` with both `ViaDiff` and
+/// `ViaDebug` in scope recurses into the field if its type implements `Diff`,
+/// and otherwise compares its `Debug` output.
+pub struct DiffField<'a, T: ?Sized>(pub &'a T, pub &'a T);
+
+pub trait ViaDiff {
+    fn diff_field(&self, path: &str, diffs: &mut Vec<FieldDiff>);
+}
+
+impl<T> ViaDiff for DiffField<'_, T>
+where
+    T: ?Sized + Diff,
+{
+    fn diff_field(&self, path: &str, diffs: &mut Vec<FieldDiff>) {
+        self.0.diff_fields(self.1, path, diffs);
+    }
+}
+
+pub trait ViaDebug {
+    fn diff_field(&self, path: &str, diffs: &mut Vec<FieldDiff>);
+}
+
+impl<T> ViaDebug for &DiffField<'_, T>
+where
+    T: ?Sized + Debug,
+{
+    fn diff_field(&self, path: &str, diffs: &mut Vec<FieldDiff>) {
+        diff_debug(path, self.0, self.1, diffs);
+    }
+}
+
+/// Records a difference at `path` if `left` and `right` print differently.
+pub fn diff_debug<T>(path: &str, left: &T, right: &T, diffs: &mut Vec<FieldDiff>)
+where
+    T: ?Sized + Debug,
+{
+    diff_strings(path, &format!("{:?}", left), &format!("{:?}", right), diffs);
+}
+
+/// Records a difference at `path` if the already printed `left` and `right`
+/// differ.
+pub fn diff_strings(path: &str, left: &str, right: &str, diffs: &mut Vec<FieldDiff>) {
+    if left != right {
+        diffs.push(FieldDiff {
+            path: path.to_owned(),
+            left: left.to_owned(),
+            right: right.to_owned(),
+        });
+    }
+}
+
+/// The path to the field `name` inside the value at `path`.
+pub fn field_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
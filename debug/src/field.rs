@@ -0,0 +1,33 @@
+use std::fmt::{self, Debug};
+
+/// Implemented for a `CustomDebug` type once for each field printed through
+/// one of its options, writing the `I`th field as it appears in the type's
+/// `Debug` output.
+pub trait DebugField<const I: usize> {
+    fn fmt_field(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+/// A value of type `T` viewed through the `Debug` output of its `I`th field,
+/// so that `debug_fields` can return a formatted field as a `&dyn Debug`
+/// borrowed from the value.
+#[repr(transparent)]
+pub struct Field<T: ?Sized, const I: usize>(T);
+
+impl<T, const I: usize> Field<T, I>
+where
+    T: ?Sized + DebugField<I>,
+{
+    pub fn new(value: &T) -> &Self {
+        // SAFETY: Field is a #[repr(transparent)] wrapper around T.
+        unsafe { &*(value as *const T as *const Self) }
+    }
+}
+
+impl<T, const I: usize> Debug for Field<T, I>
+where
+    T: ?Sized + DebugField<I>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt_field(f)
+    }
+}
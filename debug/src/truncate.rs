@@ -0,0 +1,105 @@
+use std::fmt::{self, Debug};
+
+/// Prints the first items of a collection as a list, followed by a count of
+/// the items left out, like `[1, 2, ... (3 more)]`.
+pub struct MaxItems<'a, T: ?Sized>(pub &'a T, pub usize);
+
+impl<'a, T> Debug for MaxItems<'a, T>
+where
+    T: ?Sized,
+    &'a T: IntoIterator,
+    <&'a T as IntoIterator>::Item: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        let mut items = self.0.into_iter();
+        list.entries(items.by_ref().take(self.1));
+        let more = items.count();
+        if more > 0 {
+            list.entry(&format_args!("... ({} more)", more));
+        }
+        list.finish()
+    }
+}
+
+/// Prints the first items of a set, followed by a count of the items left out,
+/// like `{1, 2, ... (3 more)}`.
+pub struct MaxSetItems<'a, T: ?Sized>(pub &'a T, pub usize);
+
+impl<'a, T> Debug for MaxSetItems<'a, T>
+where
+    T: ?Sized,
+    &'a T: IntoIterator,
+    <&'a T as IntoIterator>::Item: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut set = f.debug_set();
+        let mut items = self.0.into_iter();
+        set.entries(items.by_ref().take(self.1));
+        let more = items.count();
+        if more > 0 {
+            set.entry(&format_args!("... ({} more)", more));
+        }
+        set.finish()
+    }
+}
+
+/// Prints the first entries of a map, followed by a count of the entries left
+/// out, like `{1: 'a', 2: 'b', ... (3 more)}`.
+pub struct MaxEntries<'a, T: ?Sized>(pub &'a T, pub usize);
+
+impl<'a, T, K, V> Debug for MaxEntries<'a, T>
+where
+    T: ?Sized,
+    &'a T: IntoIterator<Item = (K, V)>,
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // DebugMap has no way to print the count as a bare entry, so the
+        // entries are printed as a set of `key: value` items, which looks the
+        // same.
+        let mut map = f.debug_set();
+        let mut entries = self.0.into_iter();
+        map.entries(entries.by_ref().take(self.1).map(|(k, v)| Entry(k, v)));
+        let more = entries.count();
+        if more > 0 {
+            map.entry(&format_args!("... ({} more)", more));
+        }
+        map.finish()
+    }
+}
+
+struct Entry<K, V>(K, V);
+
+impl<K, V> Debug for Entry<K, V>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)?;
+        f.write_str(": ")?;
+        self.1.fmt(f)
+    }
+}
+
+/// Prints the first characters of a string, followed by a count of the
+/// characters left out, like `"abc"... (3 more)`.
+pub struct MaxLen<'a, T: ?Sized>(pub &'a T, pub usize);
+
+impl<T> Debug for MaxLen<'_, T>
+where
+    T: ?Sized + AsRef<str>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = self.0.as_ref();
+        match s.char_indices().nth(self.1) {
+            None => Debug::fmt(s, f),
+            Some((end, _)) => {
+                Debug::fmt(&s[..end], f)?;
+                write!(f, "... ({} more)", s[end..].chars().count())
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+// Generate a Debug impl for enums too. Unit variants print just their name,
+// tuple variants print like a tuple struct and struct variants like a struct,
+// the same as the standard library's derive. Field attributes
+// #[debug = "..."] apply to variant fields as well.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub enum Packet<T> {
+    Empty,
+    Data(T, #[debug = "0x{:02x}"] u8),
+    Header {
+        name: &'static str,
+        #[debug = "0b{:08b}"]
+        flags: u8,
+    },
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn main() {
+    let empty: Packet<u8> = Packet::Empty;
+    assert_eq!(format!("{:?}", empty), "Empty");
+
+    let data = Packet::Data("F", 0x1c);
+    assert_eq!(format!("{:?}", data), r#"Data("F", 0x1c)"#);
+
+    let header: Packet<u8> = Packet::Header {
+        name: "F",
+        flags: 0b00011100,
+    };
+    assert_eq!(
+        format!("{:?}", header),
+        r#"Header { name: "F", flags: 0b00011100 }"#,
+    );
+    assert_eq!(
+        format!("{:#?}", header),
+        "Header {\n    name: \"F\",\n    flags: 0b00011100,\n}",
+    );
+}
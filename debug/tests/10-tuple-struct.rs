@@ -0,0 +1,20 @@
+// Tuple structs and unit structs print the same way the standard library's
+// derive prints them, with #[debug = "..."] formats applied to tuple fields.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Mask(#[debug = "0b{:08b}"] u8);
+
+#[derive(CustomDebug)]
+pub struct Pair<T>(T, &'static str);
+
+#[derive(CustomDebug)]
+pub struct Unit;
+
+fn main() {
+    assert_eq!(format!("{:?}", Mask(0b00011100)), "Mask(0b00011100)");
+    assert_eq!(format!("{:?}", Pair(1, "F")), r#"Pair(1, "F")"#);
+    assert_eq!(format!("{:#?}", Pair(1, "F")), "Pair(\n    1,\n    \"F\",\n)");
+    assert_eq!(format!("{:?}", Unit), "Unit");
+}
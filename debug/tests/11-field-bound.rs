@@ -0,0 +1,49 @@
+// A #[debug(bound = "...")] attribute on a field replaces only the bounds
+// inferred from that field's type, while bounds inferred from the other fields
+// are kept.
+//
+//     impl<T: Trait, U> Debug for Wrapper<T, U>
+//     where
+//         T::Value: Debug,
+//         U: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id, u8>>();
+
+    let wrapper = Wrapper::<Id, _> {
+        field: Field { values: vec![1] },
+        normal: "F",
+    };
+    assert_eq!(
+        format!("{:?}", wrapper),
+        r#"Wrapper { field: Field { values: [1] }, normal: "F" }"#,
+    );
+}
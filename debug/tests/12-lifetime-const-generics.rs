@@ -0,0 +1,38 @@
+// The generated impl carries over every generic parameter of the input,
+// including lifetimes and const generics, along with the input's own where
+// clause. Inferred bounds are added to that where clause.
+//
+//     impl<'a, T: ?Sized, const N: usize> Debug for View<'a, T, N>
+//     where
+//         T: AsRef<str>,
+//         T: Debug,
+//     {...}
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct View<'a, T: ?Sized, const N: usize>
+where
+    T: AsRef<str>,
+{
+    name: &'a T,
+    buf: [u8; N],
+}
+
+#[derive(CustomDebug)]
+pub enum Slice<'a, const N: usize = 2> {
+    Borrowed(&'a [u8]),
+    Owned([u8; N]),
+}
+
+fn main() {
+    let view = View {
+        name: "F",
+        buf: [1, 2],
+    };
+    assert_eq!(format!("{:?}", view), r#"View { name: "F", buf: [1, 2] }"#);
+
+    let slice: Slice = Slice::Owned([3, 4]);
+    assert_eq!(format!("{:?}", slice), "Owned([3, 4])");
+    assert_eq!(format!("{:?}", Slice::<3>::Borrowed(&[5])), "Borrowed([5])");
+}
@@ -0,0 +1,52 @@
+// Sensitive fields can be kept out of the output. A #[debug(skip)] field is
+// omitted entirely and the output ends in `..` to show that something was left
+// out. A #[debug(redact)] field prints `<redacted>`, or the given mask, in place
+// of its value, and a #[debug(redact_len)] field prints only its length.
+//
+// Skipped and redacted fields do not need to implement Debug.
+
+use derive_debug::CustomDebug;
+
+struct Secret;
+
+impl Secret {
+    fn len(&self) -> usize {
+        6
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Login {
+    user: &'static str,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact = "****")]
+    pin: u16,
+    #[debug(redact_len)]
+    token: Secret,
+    #[debug(skip)]
+    session: Secret,
+}
+
+#[derive(CustomDebug)]
+pub enum Credential<T> {
+    Key(#[debug(skip)] T, u8),
+}
+
+fn main() {
+    let login = Login {
+        user: "F",
+        password: "hunter2".to_owned(),
+        pin: 1234,
+        token: Secret,
+        session: Secret,
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"Login { user: "F", password: <redacted>, pin: ****, token: <redacted len=6>, .. }"#,
+    );
+
+    struct NotDebug;
+    let key = Credential::Key(NotDebug, 1);
+    assert_eq!(format!("{:?}", key), "Key(1, ..)");
+}
@@ -0,0 +1,33 @@
+// Attributes other than #[debug...] on fields belong to someone else, like doc
+// comments or attributes of other derives, and are left alone.
+//
+// Besides #[debug = "..."], options can be given in list form and combined
+// within one attribute, with `fmt = "..."` giving the format string.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    /// The name of the field.
+    #[allow(dead_code)]
+    name: &'static str,
+    /// Which bits are set.
+    #[debug(fmt = "0b{:08b}")]
+    bitmask: u8,
+    #[debug(fmt = "{:x}", bound = "")]
+    #[doc = "A hex value."]
+    hex: u32,
+}
+
+fn main() {
+    let f = Field {
+        name: "F",
+        bitmask: 0b00011100,
+        hex: 0xbeef,
+    };
+
+    let debug = format!("{:?}", f);
+    let expected = r#"Field { name: "F", bitmask: 0b00011100, hex: beef }"#;
+
+    assert_eq!(debug, expected);
+}
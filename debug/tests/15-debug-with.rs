@@ -0,0 +1,61 @@
+// A #[debug(with = "path")] attribute names a function that formats the field
+// in place of the field's own Debug impl. The function is called with a
+// reference to the field and the Formatter, the same as Debug::fmt, so deref
+// coercions apply to its first argument. The path may also be given without
+// quotes.
+//
+// The field's type does not need to implement Debug, so no bound is inferred
+// from it; whatever the function requires can be given with `bound`.
+
+use derive_debug::CustomDebug;
+use std::fmt;
+
+mod hex {
+    use std::fmt;
+
+    pub fn fmt_bytes(bytes: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in bytes {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+fn abbreviate(hash: &str, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}…", &hash[..7])
+}
+
+pub struct NotDebug<T>(T);
+
+fn fmt_not_debug<T: fmt::Display>(value: &NotDebug<T>, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "<{}>", value.0)
+}
+
+#[derive(CustomDebug)]
+pub struct Commit<T> {
+    #[debug(with = "abbreviate")]
+    hash: String,
+    #[debug(with = hex::fmt_bytes)]
+    payload: Vec<u8>,
+    #[debug(with = "fmt_not_debug", bound = "T: fmt::Display")]
+    tag: NotDebug<T>,
+}
+
+#[derive(CustomDebug)]
+pub enum Frame {
+    Data(#[debug(with = "hex::fmt_bytes")] [u8; 2]),
+}
+
+fn main() {
+    let commit = Commit {
+        hash: "4c5b89d0a9e2".to_owned(),
+        payload: vec![0xde, 0xad],
+        tag: NotDebug(1),
+    };
+    assert_eq!(
+        format!("{:?}", commit),
+        "Commit { hash: 4c5b89d…, payload: dead, tag: <1> }",
+    );
+
+    assert_eq!(format!("{:?}", Frame::Data([0xbe, 0xef])), "Data(beef)");
+}
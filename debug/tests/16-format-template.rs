@@ -0,0 +1,70 @@
+// Beyond #[debug = "..."], a field can be formatted by a format string with
+// its own arguments, which may be any expressions over `self`:
+//
+//     #[debug("{}/{}", self.num, self.den)]
+//
+// The same attribute on a struct, or on an enum variant, replaces the whole
+// output with the template. Placeholders in a template may refer to named
+// fields directly, like `{x}`.
+//
+// A field whose value is not itself printed does not get a Debug bound
+// inferred from its type. A template using other traits of a type parameter
+// needs those bounds spelled out.
+
+use derive_debug::CustomDebug;
+
+pub struct Ratio {
+    num: u32,
+    den: u32,
+}
+
+#[derive(CustomDebug)]
+pub struct Measure {
+    #[debug("{}/{}", self.ratio.num, self.ratio.den)]
+    ratio: Ratio,
+    #[debug("0b{:08b}")]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+#[debug("Point({x}, {y:.1})")]
+#[debug(bound = "T: std::fmt::Display")]
+pub struct Point<T> {
+    x: i32,
+    y: T,
+}
+
+#[derive(CustomDebug)]
+#[debug("{}+{}i", self.0, self.1)]
+pub struct Complex(f32, f32);
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    #[debug("Circle(r={radius})")]
+    Circle { radius: u32 },
+    #[debug("Rect({width}x{height}, {unit})", unit = "px")]
+    Rect { width: u32, height: u32 },
+    Line(u32),
+}
+
+fn main() {
+    let measure = Measure {
+        ratio: Ratio { num: 3, den: 4 },
+        bitmask: 0b00011100,
+    };
+    assert_eq!(
+        format!("{:?}", measure),
+        "Measure { ratio: 3/4, bitmask: 0b00011100 }",
+    );
+
+    let point = Point { x: 1, y: 2.25 };
+    assert_eq!(format!("{:?}", point), "Point(1, 2.2)");
+    assert_eq!(format!("{:?}", Complex(1.0, 2.5)), "1+2.5i");
+
+    assert_eq!(format!("{:?}", Shape::Circle { radius: 2 }), "Circle(r=2)");
+    assert_eq!(
+        format!("{:?}", Shape::Rect { width: 3, height: 4 }),
+        "Rect(3x4, px)",
+    );
+    assert_eq!(format!("{:?}", Shape::Line(5)), "Line(5)");
+}
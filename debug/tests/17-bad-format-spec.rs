@@ -0,0 +1,14 @@
+// Format strings in #[debug] attributes are checked by the macro, so that a
+// malformed one is reported on the attribute itself rather than somewhere in
+// the generated code. This is a compile_fail test.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    name: &'static str,
+    #[debug = "{:08q}"]
+    bitmask: u8,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// The placeholders of a format string must match the arguments it is given.
+// A field's format string without arguments is given just the field, so it
+// needs exactly one placeholder. This is a compile_fail test.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    name: &'static str,
+    #[debug = "{:08b} / {:08b}"]
+    bitmask: u8,
+}
+
+fn main() {}
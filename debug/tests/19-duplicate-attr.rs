@@ -0,0 +1,14 @@
+// A field can have only one format. Rather than silently using the last of
+// several, the macro reports the duplicate. This is a compile_fail test.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    name: &'static str,
+    #[debug = "0b{:08b}"]
+    #[debug(fmt = "0x{:02x}")]
+    bitmask: u8,
+}
+
+fn main() {}
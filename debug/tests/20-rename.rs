@@ -0,0 +1,37 @@
+// The printed names of fields, structs, enums and variants can differ from
+// their Rust identifiers through #[debug(rename = "...")]. Raw identifiers are
+// printed without their `r#` prefix, the same as the standard library's derive.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(rename = "Token")]
+pub struct RawToken {
+    r#type: &'static str,
+    #[debug(rename = "type")]
+    kind: u8,
+    #[debug(rename = "mask", fmt = "0b{:08b}")]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub enum r#Event {
+    #[debug(rename = "Open")]
+    Opened { r#ref: u8 },
+    r#Close(u8),
+}
+
+fn main() {
+    let token = RawToken {
+        r#type: "F",
+        kind: 1,
+        bitmask: 0b00011100,
+    };
+    assert_eq!(
+        format!("{:?}", token),
+        r#"Token { type: "F", type: 1, mask: 0b00011100 }"#,
+    );
+
+    assert_eq!(format!("{:?}", Event::Opened { r#ref: 2 }), "Open { ref: 2 }");
+    assert_eq!(format!("{:?}", Event::Close(3)), "Close(3)");
+}
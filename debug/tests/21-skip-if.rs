@@ -0,0 +1,46 @@
+// A #[debug(skip_if = "path")] attribute names a predicate that is called with
+// a reference to the field each time the value is formatted. When it returns
+// true the field is left out, and the output ends in `..` to show that
+// something was left out, the same as for #[debug(skip)].
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Options {
+    name: &'static str,
+    #[debug(skip_if = "Option::is_none")]
+    timeout: Option<u32>,
+    #[debug(skip_if = Vec::is_empty)]
+    args: Vec<&'static str>,
+}
+
+fn is_zero(n: &u8) -> bool {
+    *n == 0
+}
+
+#[derive(CustomDebug)]
+pub enum Retry {
+    Times(#[debug(skip_if = "is_zero")] u8, #[debug(skip)] u8),
+}
+
+fn main() {
+    let empty = Options {
+        name: "F",
+        timeout: None,
+        args: vec![],
+    };
+    assert_eq!(format!("{:?}", empty), r#"Options { name: "F", .. }"#);
+
+    let full = Options {
+        name: "F",
+        timeout: Some(3),
+        args: vec!["-v"],
+    };
+    assert_eq!(
+        format!("{:?}", full),
+        r#"Options { name: "F", timeout: Some(3), args: ["-v"] }"#,
+    );
+
+    assert_eq!(format!("{:?}", Retry::Times(0, 1)), "Times(..)");
+    assert_eq!(format!("{:?}", Retry::Times(2, 1)), "Times(2, ..)");
+}
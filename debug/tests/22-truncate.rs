@@ -0,0 +1,80 @@
+// Long collections and strings can be cut short. A #[debug(max_items = N)]
+// field prints its first N items followed by a count of the rest, and a
+// #[debug(max_len = N)] field prints its first N characters followed by a count
+// of the rest.
+//
+// Given on the struct or enum, these limits apply to every field that looks
+// like a collection (Vec, HashMap, arrays, slices, ...) or a string (String or
+// str) by the name of its type.
+
+use derive_debug::CustomDebug;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(CustomDebug)]
+pub struct Log {
+    #[debug(max_items = 2)]
+    lines: Vec<u32>,
+    #[debug(max_len = 5)]
+    message: String,
+    #[debug(max_items = 1)]
+    tags: &'static [&'static str],
+}
+
+#[derive(CustomDebug)]
+#[debug(max_items = 2, max_len = 3)]
+pub struct Defaults {
+    ids: [u8; 4],
+    map: BTreeMap<u8, char>,
+    set: BTreeSet<u8>,
+    name: &'static str,
+    #[debug(max_items = 3)]
+    counts: Vec<u8>,
+    id: u64,
+}
+
+#[derive(CustomDebug)]
+#[debug(max_items = 1)]
+pub enum Batch {
+    Items(Vec<u8>),
+}
+
+fn main() {
+    let log = Log {
+        lines: vec![1, 2, 3, 4, 5],
+        message: "hello world".to_owned(),
+        tags: &["a"],
+    };
+    assert_eq!(
+        format!("{:?}", log),
+        r#"Log { lines: [1, 2, ... (3 more)], message: "hello"... (6 more), tags: ["a"] }"#,
+    );
+
+    let defaults = Defaults {
+        ids: [1, 2, 3, 4],
+        map: [(1, 'a'), (2, 'b'), (3, 'c')].into_iter().collect(),
+        set: [1, 2, 3].into_iter().collect(),
+        name: "abc",
+        counts: vec![1, 2, 3, 4],
+        id: 123456,
+    };
+    assert_eq!(
+        format!("{:?}", defaults),
+        "Defaults { ids: [1, 2, ... (2 more)], map: {1: 'a', 2: 'b', ... (1 more)}, \
+         set: {1, 2, ... (1 more)}, name: \"abc\", counts: [1, 2, 3, ... (1 more)], id: 123456 }",
+    );
+
+    // Maps and sets keep their shape, whether or not they are truncated.
+    let defaults = Defaults {
+        map: [(1, 'a')].into_iter().collect(),
+        set: [1].into_iter().collect(),
+        ..defaults
+    };
+    assert!(format!("{:?}", defaults).contains("map: {1: 'a'}, set: {1},"));
+    assert!(format!("{:#?}", defaults).contains("map: {\n        1: 'a',\n    },"));
+    assert!(format!("{:#?}", defaults).contains("set: {\n        1,\n    },"));
+
+    assert_eq!(
+        format!("{:?}", Batch::Items(vec![7, 8])),
+        "Items([7, ... (1 more)])",
+    );
+}
@@ -0,0 +1,41 @@
+// A #[debug(transparent)] struct with a single field prints as just that
+// field, so that newtypes like `UserId(42)` print as `42`. The field's own
+// Debug impl does all the formatting, including in alternate mode, and the
+// field's options still apply.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Wrapper<T> {
+    inner: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Mask(#[debug = "0b{:08b}"] u8);
+
+#[derive(CustomDebug)]
+pub struct User {
+    id: UserId,
+    tags: Wrapper<Vec<&'static str>>,
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", UserId(42)), "42");
+    assert_eq!(format!("{:?}", Mask(0b00011100)), "0b00011100");
+
+    let user = User {
+        id: UserId(42),
+        tags: Wrapper { inner: vec!["a"] },
+    };
+    assert_eq!(format!("{:?}", user), r#"User { id: 42, tags: ["a"] }"#);
+    assert_eq!(
+        format!("{:#?}", user),
+        "User {\n    id: 42,\n    tags: [\n        \"a\",\n    ],\n}",
+    );
+}
@@ -0,0 +1,108 @@
+// derive(CustomDisplay) writes a Display impl from the same templates as
+// #[debug("...")]. A struct gives one template; an enum gives one per variant,
+// where unit variants without a template display their own name.
+//
+// Type parameters are bounded by the traits the templates format them with,
+// like `Display` for `{}` or `Debug` for `{:?}`, only in the fields the
+// templates use.
+
+use derive_debug::{CustomDebug, CustomDisplay};
+use std::fmt::Display;
+
+#[derive(CustomDisplay)]
+#[display("({x}, {y})")]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDisplay)]
+#[display("{} -> {}", self.0, self.1)]
+pub struct Edge<T>(T, T);
+
+#[derive(CustomDisplay, CustomDebug)]
+pub enum Shape {
+    #[display("circle of radius {radius}")]
+    Circle { radius: u32 },
+    #[display("{width}x{height} rectangle")]
+    Rect { width: u32, height: u32 },
+    Empty,
+}
+
+// Tuple fields are the template's positional arguments, or can be named like
+// `_0`, as in CustomDebug templates.
+#[derive(CustomDisplay)]
+#[allow(non_camel_case_types)]
+pub enum Error {
+    #[display("I/O error: {0}")]
+    Io(std::io::Error),
+    #[display("invalid code {} at line {}")]
+    Code(u32, usize),
+    #[display("{_1} ({_0})")]
+    Named(u32, &'static str),
+    r#type,
+}
+
+#[derive(CustomDisplay)]
+#[display("<{:?}>", self.0)]
+#[display(bound = "T: std::fmt::Debug")]
+pub struct Wrapper<T>(T);
+
+#[derive(CustomDisplay)]
+#[display("#{id}")]
+pub struct Tagged<T> {
+    id: u32,
+    payload: T,
+}
+
+#[derive(CustomDisplay)]
+pub enum Pair<T, U> {
+    #[display("{0} and {1:?}")]
+    Both(T, U),
+    #[display("{left}")]
+    Left { left: T, right: U },
+}
+
+fn assert_display<T: Display>() {}
+
+fn main() {
+    assert_eq!(Point { x: 1, y: -2 }.to_string(), "(1, -2)");
+    assert_eq!(Edge("a", "b").to_string(), "a -> b");
+
+    assert_eq!(
+        Shape::Circle { radius: 3 }.to_string(),
+        "circle of radius 3",
+    );
+    assert_eq!(Shape::Rect { width: 2, height: 4 }.to_string(), "2x4 rectangle");
+    assert_eq!(Shape::Empty.to_string(), "Empty");
+    assert_eq!(format!("{:?}", Shape::Empty), "Empty");
+
+    let io = std::io::Error::new(std::io::ErrorKind::Other, "disk full");
+    assert_eq!(Error::Io(io).to_string(), "I/O error: disk full");
+    assert_eq!(Error::Code(7, 12).to_string(), "invalid code 7 at line 12");
+    assert_eq!(Error::Named(404, "not found").to_string(), "not found (404)");
+    assert_eq!(Error::r#type.to_string(), "type");
+
+    struct NotDisplay;
+    impl std::fmt::Debug for NotDisplay {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("NotDisplay")
+        }
+    }
+    assert_display::<Wrapper<NotDisplay>>();
+    assert_eq!(Wrapper(NotDisplay).to_string(), "<NotDisplay>");
+
+    assert_display::<Tagged<NotDisplay>>();
+    let tagged = Tagged {
+        id: 7,
+        payload: NotDisplay,
+    };
+    assert_eq!(tagged.to_string(), "#7");
+    assert_display::<Pair<u8, NotDisplay>>();
+    assert_eq!(Pair::<_, NotDisplay>::Both(1, NotDisplay).to_string(), "1 and NotDisplay");
+    let left = Pair::Left {
+        left: 2,
+        right: NotDisplay,
+    };
+    assert_eq!(left.to_string(), "2");
+}
@@ -0,0 +1,105 @@
+// #[debug(diff)] also generates a `debug_diff` method listing the fields that
+// differ between two values, each with its path and both values as printed by
+// Debug. Fields whose type also derives it are compared field by field, so a
+// failing assertion on a large struct can show only what changed. Truncated
+// fields are compared in full, including the items their output leaves out.
+
+use derive_debug::{CustomDebug, FieldDiff};
+
+#[derive(CustomDebug)]
+#[debug(diff)]
+pub struct Config {
+    name: String,
+    retry: Retry,
+    #[debug(redact)]
+    password: String,
+    #[debug(skip)]
+    cache: Vec<u8>,
+    ports: Vec<u16>,
+}
+
+#[derive(CustomDebug)]
+#[debug(diff)]
+pub struct Retry {
+    attempts: u32,
+    #[debug(rename = "backoff_ms")]
+    backoff: u64,
+}
+
+#[derive(CustomDebug)]
+#[debug(diff)]
+pub enum Shape {
+    Circle { radius: u32 },
+    Rect(u32, u32),
+}
+
+#[derive(CustomDebug)]
+#[debug(diff)]
+pub struct Pair<T>(T, Shape);
+
+#[derive(CustomDebug)]
+#[debug(diff)]
+pub struct Limits {
+    #[debug(max_items = 2)]
+    ports: Vec<u16>,
+    #[debug(max_len = 5)]
+    host: String,
+}
+
+fn config() -> Config {
+    Config {
+        name: "server".to_owned(),
+        retry: Retry {
+            attempts: 3,
+            backoff: 100,
+        },
+        password: "hunter2".to_owned(),
+        cache: vec![1, 2, 3],
+        ports: vec![80, 443],
+    }
+}
+
+fn diff(path: &str, left: &str, right: &str) -> FieldDiff {
+    FieldDiff {
+        path: path.to_owned(),
+        left: left.to_owned(),
+        right: right.to_owned(),
+    }
+}
+
+fn main() {
+    assert_eq!(config().debug_diff(&config()), vec![]);
+
+    let mut other = config();
+    other.retry.backoff = 200;
+    other.password = "correct horse".to_owned();
+    other.cache.clear();
+    other.ports.push(8080);
+    assert_eq!(
+        config().debug_diff(&other),
+        vec![
+            diff("retry.backoff_ms", "100", "200"),
+            diff("ports", "[80, 443]", "[80, 443, 8080]"),
+        ],
+    );
+
+    let diffs = Pair("a", Shape::Rect(1, 2)).debug_diff(&Pair("b", Shape::Rect(1, 3)));
+    assert_eq!(diffs, vec![diff("0", "\"a\"", "\"b\""), diff("1.1", "2", "3")]);
+    assert_eq!(diffs[1].to_string(), "1.1: 2 != 3");
+
+    let limits = |ports: Vec<u16>, host: &str| Limits {
+        ports,
+        host: host.to_owned(),
+    };
+    assert_eq!(
+        limits(vec![1, 2, 3], "localhost").debug_diff(&limits(vec![1, 2, 4], "localhorse")),
+        vec![
+            diff("ports", "[1, 2, 3]", "[1, 2, 4]"),
+            diff("host", "\"localhost\"", "\"localhorse\""),
+        ],
+    );
+
+    let diffs = Shape::Circle { radius: 1 }.debug_diff(&Shape::Rect(1, 1));
+    assert_eq!(diffs, vec![diff("", "Circle { radius: 1 }", "Rect(1, 1)")]);
+    assert_eq!(diffs[0].to_string(), "Circle { radius: 1 } != Rect(1, 1)");
+}
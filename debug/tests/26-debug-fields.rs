@@ -0,0 +1,88 @@
+// A #[debug(fields)] struct also gets a FIELD_NAMES const and a `debug_fields`
+// method, giving the fields in its Debug output as key/value pairs for
+// structured loggers. Each value prints exactly as the field does in the Debug
+// output, so formats, redaction and the other options apply.
+//
+// Without the option, neither is generated, leaving the names free for the
+// type's own items.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+#[debug(fields)]
+pub struct Request<'a, T> {
+    method: &'a str,
+    #[debug = "0b{:08b}"]
+    flags: u8,
+    #[debug(redact)]
+    token: String,
+    #[debug(skip)]
+    body: Vec<u8>,
+    #[debug("{}ms", self.timeout_ms)]
+    timeout_ms: u64,
+    #[debug(rename = "user", skip_if = "Option::is_none")]
+    user_id: Option<T>,
+}
+
+#[derive(CustomDebug)]
+#[debug(fields)]
+pub struct Point(i32, #[debug = "{:+}"] i32);
+
+#[derive(CustomDebug)]
+#[debug(fields)]
+pub struct Unit;
+
+#[derive(CustomDebug)]
+pub struct Plain {
+    x: u32,
+}
+
+impl Plain {
+    const FIELD_NAMES: u32 = 1;
+
+    fn debug_fields(&self) -> u32 {
+        self.x + Self::FIELD_NAMES
+    }
+}
+
+fn pairs<'a>(fields: impl Iterator<Item = (&'static str, &'a dyn Debug)>) -> Vec<String> {
+    fields
+        .map(|(name, value)| format!("{}={:?}", name, value))
+        .collect()
+}
+
+fn main() {
+    assert_eq!(
+        Request::<u32>::FIELD_NAMES,
+        ["method", "flags", "token", "timeout_ms", "user"],
+    );
+
+    let mut request = Request {
+        method: "GET",
+        flags: 5,
+        token: "secret".to_owned(),
+        body: vec![1, 2, 3],
+        timeout_ms: 250,
+        user_id: None,
+    };
+    assert_eq!(
+        pairs(request.debug_fields()),
+        [
+            "method=\"GET\"",
+            "flags=0b00000101",
+            "token=<redacted>",
+            "timeout_ms=250ms",
+        ],
+    );
+    request.user_id = Some(7u32);
+    assert_eq!(pairs(request.debug_fields()).last().unwrap(), "user=Some(7)");
+
+    assert_eq!(Point::FIELD_NAMES, ["0", "1"]);
+    assert_eq!(pairs(Point(1, 2).debug_fields()), ["0=1", "1=+2"]);
+
+    assert!(Unit::FIELD_NAMES.is_empty());
+    assert_eq!(Unit.debug_fields().count(), 0);
+
+    assert_eq!(Plain { x: 1 }.debug_fields(), 2);
+}
@@ -0,0 +1,74 @@
+// #[debug(max_depth = N)] prints values nested more than N levels below a
+// value of the type as `...`, so that recursive structures like trees print a
+// bounded amount of output. Every CustomDebug value counts as a level, so the
+// limit also applies through other CustomDebug types in between.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(max_depth = 2)]
+pub struct Node {
+    value: u32,
+    children: Vec<Node>,
+}
+
+#[derive(CustomDebug)]
+#[debug(max_depth = 3)]
+pub enum Expr {
+    Lit(i64),
+    Neg(Box<Expr>),
+    Call(Call),
+}
+
+#[derive(CustomDebug)]
+pub struct Call {
+    name: &'static str,
+    args: Vec<Expr>,
+}
+
+// A limit too large to reach is the same as no limit, even for a value nested
+// below others.
+#[derive(CustomDebug)]
+#[debug(max_depth = 18446744073709551615)]
+pub struct Unlimited(Option<Box<Unlimited>>);
+
+#[derive(CustomDebug)]
+pub struct Outer(Unlimited);
+
+fn node(value: u32, children: Vec<Node>) -> Node {
+    Node { value, children }
+}
+
+fn main() {
+    let tree = node(1, vec![node(2, vec![node(3, vec![])]), node(4, vec![])]);
+    assert_eq!(
+        format!("{:?}", tree),
+        "Node { value: 1, children: [Node { value: 2, children: [...] }, \
+         Node { value: 4, children: [] }] }",
+    );
+
+    // The limit of the outermost value applies to the values nested in it.
+    let subtree = &tree.children[0];
+    assert_eq!(
+        format!("{:?}", subtree),
+        "Node { value: 2, children: [Node { value: 3, children: [] }] }",
+    );
+
+    let expr = Expr::Neg(Box::new(Expr::Call(Call {
+        name: "f",
+        args: vec![Expr::Lit(1), Expr::Neg(Box::new(Expr::Lit(2)))],
+    })));
+    assert_eq!(
+        format!("{:?}", expr),
+        "Neg(Call(Call { name: \"f\", args: [..., ...] }))",
+    );
+
+    let unlimited = Outer(Unlimited(Some(Box::new(Unlimited(None)))));
+    assert_eq!(
+        format!("{:?}", unlimited),
+        "Outer(Unlimited(Some(Unlimited(None))))",
+    );
+
+    // Depth is tracked per formatting call, so printing again starts over.
+    assert_eq!(format!("{:?}", Expr::Lit(1)), "Lit(1)");
+}
@@ -0,0 +1,44 @@
+// Shorthands for common field formats: #[debug(hex)] and #[debug(bin)] print
+// integers like `{:#x}` and `{:#b}`, and #[debug(bytes = "...")] prints byte
+// buffers like `&[u8]` and `Vec<u8>` as a hex string, or as text with invalid
+// UTF-8 replaced. In alternate mode, `bytes = "hex"` prints a hexdump.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Packet<'a> {
+    #[debug(hex)]
+    id: u32,
+    #[debug(bin)]
+    flags: u8,
+    #[debug(bytes = "hex")]
+    header: &'a [u8],
+    #[debug(bytes = "utf8_lossy")]
+    payload: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub struct Frame(#[debug(bytes = "hex")] Vec<u8>);
+
+fn main() {
+    let packet = Packet {
+        id: 255,
+        flags: 5,
+        header: &[0xde, 0xad, 0x00, 0x01],
+        payload: b"hi \xff there".to_vec(),
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { id: 0xff, flags: 0b101, header: dead0001, \
+         payload: \"hi \u{fffd} there\" }",
+    );
+
+    let frame = Frame(b"Hello, world!\n\x00\x01\x02\x03".to_vec());
+    assert_eq!(
+        format!("{:#?}", frame),
+        "Frame(\n    \
+         00000000  48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 0a 00 01  |Hello, world!...|\n    \
+         00000010  02 03                                            |..|,\n\
+         )",
+    );
+}
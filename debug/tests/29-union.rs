@@ -0,0 +1,47 @@
+// Which field of a union is initialized is unknown to its Debug impl, so a
+// union prints opaquely as `Name { .. }`. A union whose users guarantee which
+// field is initialized can instead print as that field, but since reading the
+// wrong field is undefined behavior, the option must be marked `unsafe`.
+
+use derive_debug::CustomDebug;
+use std::mem::ManuallyDrop;
+
+#[derive(CustomDebug)]
+pub union Value {
+    int: i64,
+    float: f64,
+}
+
+#[derive(CustomDebug)]
+#[debug(unsafe(union_as = "bits"))]
+pub union Register {
+    #[debug(hex)]
+    bits: u32,
+    bytes: [u8; 4],
+}
+
+// Only the `union_as` field is bounded, so Opaque<T> is Debug for any T.
+#[derive(CustomDebug)]
+pub union Opaque<T> {
+    value: ManuallyDrop<T>,
+    raw: usize,
+}
+
+#[derive(CustomDebug)]
+#[debug(rename = "Word", unsafe(union_as = "raw"))]
+pub union Word<T> {
+    value: ManuallyDrop<T>,
+    raw: usize,
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", Value { int: 1 }), "Value { .. }");
+    assert_eq!(format!("{:?}", Register { bits: 0xff00 }), "Register { bits: 0xff00 }");
+
+    struct NotDebug;
+    let opaque = Opaque::<NotDebug> { raw: 0 };
+    assert_eq!(format!("{:?}", opaque), "Opaque { .. }");
+
+    let word = Word::<NotDebug> { raw: 7 };
+    assert_eq!(format!("{:?}", word), "Word { raw: 7 }");
+}
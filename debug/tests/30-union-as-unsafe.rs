@@ -0,0 +1,14 @@
+// Printing a union as one of its fields reads that field whether or not it is
+// the one initialized, so `union_as` must be written inside `unsafe(...)`.
+// This is a compile_fail test.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(union_as = "int")]
+pub union Value {
+    int: i64,
+    float: f64,
+}
+
+fn main() {}
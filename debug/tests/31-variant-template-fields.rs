@@ -0,0 +1,86 @@
+// Inside an enum, `self` is the whole enum, so format arguments refer to the
+// fields of the variant being printed by name instead: a named field by its
+// own name and a tuple field as `_0`, `_1`, .... A template on a tuple variant
+// that lists no arguments of its own is passed the variant's fields, so that
+// `{0}` or `{}` is the first field, the same as on a tuple struct, and the
+// template can name a field directly, like `{_1}`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(diff)]
+pub enum Event {
+    #[debug("Line({0})")]
+    Line(u32),
+    #[debug("Move({}, {})")]
+    Move(i32, i32),
+    #[debug("Scroll({_1:+})")]
+    Scroll(u32, i32),
+    #[debug("Key({} {:?})", _1, _0)]
+    Key(char, &'static str),
+    Resize {
+        width: u32,
+        #[debug("{} ({:.2})", height, *width as f64 / *height as f64)]
+        height: u32,
+    },
+    Click(u32, #[debug("{}@{}", _1, _0)] u32),
+}
+
+#[derive(CustomDebug)]
+#[debug("{1}/{0}")]
+pub struct Fraction(u32, u32);
+
+// A field named like a local of the generated code does not shadow it, and
+// only the fields that a format mentions are bound, so a field named like the
+// function of a sibling's `skip_if` or `with` does not shadow it either.
+#[derive(CustomDebug)]
+#[debug(diff)]
+pub enum Shadow {
+    #[debug("f={f}")]
+    F { f: u32 },
+    Debug { debug: u32 },
+    Items {
+        #[debug(skip_if = "empty")]
+        items: Vec<u8>,
+        empty: bool,
+        #[debug(with = "hex")]
+        id: u8,
+        hex: bool,
+    },
+}
+
+fn empty(items: &[u8]) -> bool {
+    items.is_empty()
+}
+
+fn hex(id: &u8, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{:#x}", id)
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", Event::Line(3)), "Line(3)");
+    assert_eq!(format!("{:?}", Event::Move(1, -2)), "Move(1, -2)");
+    assert_eq!(format!("{:?}", Event::Scroll(0, 5)), "Scroll(+5)");
+    assert_eq!(format!("{:?}", Event::Key('a', "ctrl")), "Key(ctrl 'a')");
+    assert_eq!(
+        format!("{:?}", Event::Resize { width: 4, height: 3 }),
+        "Resize { width: 4, height: 3 (1.33) }",
+    );
+    assert_eq!(format!("{:?}", Event::Click(1, 2)), "Click(1, 2@1)");
+    let diffs = Event::Resize { width: 4, height: 3 }.debug_diff(&Event::Resize { width: 4, height: 2 });
+    assert_eq!(diffs[0].to_string(), "height: 3 (1.33) != 2 (2.00)");
+    assert_eq!(format!("{:?}", Fraction(2, 1)), "1/2");
+
+    assert_eq!(format!("{:?}", Shadow::F { f: 1 }), "f=1");
+    assert_eq!(format!("{:?}", Shadow::Debug { debug: 2 }), "Debug { debug: 2 }");
+    let items = Shadow::Items {
+        items: vec![],
+        empty: true,
+        id: 10,
+        hex: false,
+    };
+    assert_eq!(
+        format!("{:?}", items),
+        "Items { empty: true, id: 0xa, hex: false, .. }",
+    );
+}
@@ -0,0 +1,18 @@
+// The field of a #[debug(transparent)] struct is the whole output, so it
+// cannot be skipped. Rather than printing a field the user asked to hide, the
+// macro reports the attribute. This is a compile_fail test.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Secret(#[debug(skip)] u8);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Tags {
+    #[debug(rename = "t", skip_if = "Vec::is_empty")]
+    tags: Vec<u8>,
+}
+
+fn main() {}
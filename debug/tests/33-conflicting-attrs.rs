@@ -0,0 +1,31 @@
+// Options deciding how a field is printed override each other, so a field can
+// have only one of them, besides `skip_if`. Rather than silently using one of
+// several, the macro reports the conflict. This is a compile_fail test.
+
+use derive_debug::CustomDebug;
+use std::fmt;
+
+#[derive(CustomDebug)]
+pub struct Id(#[debug(with = "fmt_id", fmt = "{:x}")] u32);
+
+#[derive(CustomDebug)]
+pub struct Payload(#[debug(hex, bytes = "hex")] Vec<u8>);
+
+#[derive(CustomDebug)]
+pub struct Token(
+    #[debug(skip)]
+    #[debug(redact)]
+    String,
+);
+
+#[derive(CustomDebug)]
+pub struct Hops(#[debug(max_items = 2, redact_len)] Vec<u8>);
+
+#[derive(CustomDebug)]
+pub struct Tag(#[debug(skip_if = "Option::is_none", skip)] Option<u8>);
+
+fn fmt_id(id: &u32, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "#{}", id)
+}
+
+fn main() {}
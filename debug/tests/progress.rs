@@ -5,8 +5,33 @@ fn tests() {
     t.pass("tests/02-impl-debug.rs");
     t.pass("tests/03-custom-format.rs");
     t.pass("tests/04-type-parameter.rs");
-    //t.pass("tests/05-phantom-data.rs");
-    //t.pass("tests/06-bound-trouble.rs");
-    //t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/05-phantom-data.rs");
+    t.pass("tests/06-bound-trouble.rs");
+    t.pass("tests/07-associated-type.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enum.rs");
+    t.pass("tests/10-tuple-struct.rs");
+    t.pass("tests/11-field-bound.rs");
+    t.pass("tests/12-lifetime-const-generics.rs");
+    t.pass("tests/13-skip-redact.rs");
+    t.pass("tests/14-foreign-attrs.rs");
+    t.pass("tests/15-debug-with.rs");
+    t.pass("tests/16-format-template.rs");
+    t.compile_fail("tests/17-bad-format-spec.rs");
+    t.compile_fail("tests/18-format-arg-count.rs");
+    t.compile_fail("tests/19-duplicate-attr.rs");
+    t.pass("tests/20-rename.rs");
+    t.pass("tests/21-skip-if.rs");
+    t.pass("tests/22-truncate.rs");
+    t.pass("tests/23-transparent.rs");
+    t.pass("tests/24-display.rs");
+    t.pass("tests/25-diff.rs");
+    t.pass("tests/26-debug-fields.rs");
+    t.pass("tests/27-max-depth.rs");
+    t.pass("tests/28-presets.rs");
+    t.pass("tests/29-union.rs");
+    t.compile_fail("tests/30-union-as-unsafe.rs");
+    t.pass("tests/31-variant-template-fields.rs");
+    t.compile_fail("tests/32-transparent-skip.rs");
+    t.compile_fail("tests/33-conflicting-attrs.rs");
 }
@@ -1,8 +1,144 @@
 use proc_macro::TokenStream;
 
+use proc_macro2::{Group, Literal, Span, TokenTree};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, parse_macro_input, Error, Ident, LitInt, Result, Token};
+
 #[proc_macro]
 pub fn seq(input: TokenStream) -> TokenStream {
-    let _ = input;
+    let seq = parse_macro_input!(input as Seq);
+
+    seq.expand()
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// An invocation `N in 0..16 { ... }`, repeating the body for each value of
+/// `N` in the range.
+struct Seq {
+    var: Ident,
+    start: u64,
+    end: u64,
+    body: proc_macro2::TokenStream,
+}
+
+impl Parse for Seq {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let var = input.parse()?;
+        input.parse::<Token![in]>()?;
+        let start = input.parse::<LitInt>()?.base10_parse()?;
+        input.parse::<Token![..]>()?;
+        let end = input.parse::<LitInt>()?.base10_parse()?;
+        let content;
+        braced!(content in input);
+        let body = content.parse()?;
+        if !input.is_empty() {
+            return Err(Error::new(input.span(), "unexpected tokens after the body"));
+        }
+        Ok(Seq {
+            var,
+            start,
+            end,
+            body,
+        })
+    }
+}
+
+impl Seq {
+    /// The body repeated once for each value in the range.
+    fn expand(&self) -> Result<proc_macro2::TokenStream> {
+        (self.start..self.end)
+            .map(|n| self.substitute(self.body.clone(), n))
+            .collect()
+    }
+
+    /// Copy of `stream` with every occurrence of the variable replaced by an
+    /// unsuffixed integer literal `n`, and with identifiers joined by `~`, like
+    /// `f~N` or `f~N~_suffix`, pasted into one. Substituted and pasted tokens
+    /// keep the span of the first token written by the caller, so that errors
+    /// point at them.
+    fn substitute(
+        &self,
+        stream: proc_macro2::TokenStream,
+        n: u64,
+    ) -> Result<proc_macro2::TokenStream> {
+        let tokens = stream.into_iter().collect::<Vec<_>>();
+        let mut expanded = proc_macro2::TokenStream::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            match tokens[i] {
+                TokenTree::Ident(ref ident) if is_tilde(tokens.get(i + 1)) => {
+                    let mut pasted = self.ident_text(ident, n);
+                    i += 1;
+                    while is_tilde(tokens.get(i)) {
+                        match tokens.get(i + 1) {
+                            Some(TokenTree::Ident(next)) => pasted += &self.ident_text(next, n),
+                            _ => {
+                                return Err(Error::new(
+                                    tokens[i].span(),
+                                    "expected an identifier after `~`",
+                                ))
+                            }
+                        }
+                        i += 2;
+                    }
+                    // A raw first piece, like `r#type~N`, makes the pasted
+                    // identifier raw.
+                    if ident.to_string().starts_with("r#") {
+                        pasted.insert_str(0, "r#");
+                    }
+                    expanded.extend([TokenTree::Ident(pasted_ident(&pasted, ident.span())?)]);
+                    continue;
+                }
+                TokenTree::Ident(ref ident) if *ident == self.var => {
+                    let mut lit = Literal::u64_unsuffixed(n);
+                    lit.set_span(ident.span());
+                    expanded.extend([TokenTree::Literal(lit)]);
+                }
+                TokenTree::Group(ref group) => {
+                    let mut substituted =
+                        Group::new(group.delimiter(), self.substitute(group.stream(), n)?);
+                    substituted.set_span(group.span());
+                    expanded.extend([TokenTree::Group(substituted)]);
+                }
+                ref tt => expanded.extend([tt.clone()]),
+            }
+            i += 1;
+        }
+        Ok(expanded)
+    }
+
+    /// The text an identifier contributes to a pasted identifier, without any
+    /// `r#` prefix.
+    fn ident_text(&self, ident: &Ident, n: u64) -> String {
+        if *ident == self.var {
+            n.to_string()
+        } else {
+            ident.unraw().to_string()
+        }
+    }
+}
+
+/// The identifier spelled by `text`, or an error at `span` if `text` does not
+/// spell one, like `0x` or `r#self`.
+fn pasted_ident(text: &str, span: Span) -> Result<Ident> {
+    let error = || Error::new(span, format!("`{}` is not a valid identifier", text));
+    let tokens = text
+        .parse::<proc_macro2::TokenStream>()
+        .map_err(|_| error())?
+        .into_iter()
+        .collect::<Vec<_>>();
+    match tokens.as_slice() {
+        [TokenTree::Ident(ident)] => {
+            let mut ident = ident.clone();
+            ident.set_span(span);
+            Ok(ident)
+        }
+        _ => Err(error()),
+    }
+}
 
-    unimplemented!()
+fn is_tilde(tt: Option<&TokenTree>) -> bool {
+    matches!(tt, Some(TokenTree::Punct(punct)) if punct.as_char() == '~')
 }
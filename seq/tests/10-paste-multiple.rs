@@ -0,0 +1,24 @@
+// An identifier can be pasted together from more than two pieces, with the
+// sequence variable anywhere among them: `f~N~_suffix` pastes a suffix after
+// the number, and `REG~N~_~N` uses the number twice. A raw first piece, like
+// `r#type~N`, pastes to a raw identifier.
+
+use seq::seq;
+
+seq!(N in 0..3 {
+    fn get~N~_twice() -> u64 {
+        N * 2
+    }
+
+    const REG~N~_~N: u64 = N;
+
+    fn r#type~N() -> u64 {
+        N
+    }
+});
+
+fn main() {
+    assert_eq!(get0_twice() + get1_twice() + get2_twice(), 6);
+    assert_eq!(REG0_0 + REG1_1 + REG2_2, 3);
+    assert_eq!(type0() + r#type1() + type2(), 3);
+}
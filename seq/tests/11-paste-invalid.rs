@@ -0,0 +1,10 @@
+// Pasting pieces that do not spell an identifier is an error at the pasted
+// tokens, rather than a panic in the macro.
+
+use seq::seq;
+
+seq!(N in 0..1 {
+    fn N~x() {}
+});
+
+fn main() {}